@@ -23,7 +23,7 @@ fn main() {
 
     instance.configure_mesh_rendering_system();
 
-    let mut obj = default_objects::mesh_from_file("../Sphere.obj").expect("Could not load mesh");
+    let mut obj = default_objects::uv_sphere(1.0, 32, 16);
 
     obj.component_mut::<Transform>().position = V3::new(3.0, 1.2, 3.0);
     obj.add_component(SphereCollider::new(1.0).into());
@@ -31,34 +31,38 @@ fn main() {
     obj.component_mut::<Rigidbody>().angular_velocity = V3::new(-200.0, 0.0, 0.0);
     instance.register_object(obj);
 
-    let mut obj = default_objects::mesh_from_file("../Sphere.obj").expect("Could not load mesh");
+    let mut obj = default_objects::uv_sphere(1.0, 32, 16);
     obj.component_mut::<Transform>().position = V3::new(3.0, 0.0, 0.0);
     obj.add_component(SphereCollider::new(1.0).into());
     obj.add_component(Rigidbody::new(1.0).into());
     obj.component_mut::<Rigidbody>().lock_movement = true;
     instance.register_object(obj);
 
-    let mut obj = default_objects::mesh_from_file("../Sphere.obj").expect("Could not load mesh");
+    let mut obj = default_objects::uv_sphere(1.0, 32, 16);
     obj.component_mut::<Transform>().position = V3::new(3.0, -1.2, 3.0);
     obj.add_component(SphereCollider::new(1.0).into());
     obj.add_component(Rigidbody::new_with_gravity(100.0).into());
     obj.component_mut::<Rigidbody>().angular_velocity = V3::new(200.0, 0.0, 0.0);
     instance.register_object(obj);
 
-    let mut obj = default_objects::mesh_from_file("../Sphere.obj").expect("Could not load mesh");
+    let mut obj = default_objects::uv_sphere(1.0, 32, 16);
     obj.component_mut::<Transform>().position = V3::new(3.0, -2.0, -1.0);
     obj.add_component(SphereCollider::new(1.0).into());
     obj.add_component(Rigidbody::new(1.0).into());
     obj.component_mut::<Rigidbody>().lock_movement = true;
 
     instance.register_object(obj);
-    let mut obj = default_objects::mesh_from_file("../Sphere.obj").expect("Could not load mesh");
+    let mut obj = default_objects::uv_sphere(1.0, 32, 16);
     obj.component_mut::<Transform>().position = V3::new(3.0, 2.0, -1.0);
     obj.add_component(SphereCollider::new(1.0).into());
     obj.add_component(Rigidbody::new(1.0).into());
     obj.component_mut::<Rigidbody>().lock_movement = true;
     instance.register_object(obj);
 
+    let mut light = default_objects::directional_light();
+    light.component_mut::<Transform>().rotation = V3::new(0.0, 45.0, 30.0);
+    instance.register_object(light);
+
     let physics_manager = PhysicsManager::default();
 
     unsafe { CAMERA_ID = instance.register_object(default_objects::camera(true)) };
@@ -75,7 +79,7 @@ fn main() {
 
         handle_fps(&mut fps_timer, &mut frames);
 
-        let poll: Vec<_> = instance.event_pump.poll_iter().collect();
+        let poll = instance.poll_events();
 
         for event in poll {
             handle_input_event(event, &mut instance);
@@ -120,7 +124,7 @@ fn handle_input_event(event: Event, instance: &mut Instance) {
             xrel,
             yrel,
         } => {
-            cam_transform.rotation.z += INCREASE_ROTATION * xrel as f32;
+            cam_transform.rotation.z -= INCREASE_ROTATION * xrel as f32;
             cam_transform.rotation.y += INCREASE_ROTATION * yrel as f32;
         }
         Event::KeyDown {
@@ -141,16 +145,16 @@ fn handle_input_event(event: Event, instance: &mut Instance) {
                     cam_transform.position -= cam_transform.fwd() * INCREASE_POSITION;
                 }
                 Keycode::A => {
-                    cam_transform.position += cam_transform.right() * INCREASE_POSITION;
+                    cam_transform.position -= cam_transform.right() * INCREASE_POSITION;
                 }
                 Keycode::D => {
-                    cam_transform.position -= cam_transform.right() * INCREASE_POSITION;
+                    cam_transform.position += cam_transform.right() * INCREASE_POSITION;
                 }
                 Keycode::Left => {
-                    cam_transform.rotation.z -= INCREASE_ROTATION_KEY;
+                    cam_transform.rotation.z += INCREASE_ROTATION_KEY;
                 }
                 Keycode::Right => {
-                    cam_transform.rotation.z += INCREASE_ROTATION_KEY;
+                    cam_transform.rotation.z -= INCREASE_ROTATION_KEY;
                 }
                 Keycode::Up => {
                     cam_transform.rotation.y -= INCREASE_ROTATION_KEY;
@@ -3,6 +3,8 @@ use rndr_core::default_objects;
 use rndr_core::events::{Event, Keycode};
 use rndr_core::prelude::Instance;
 
+use rndr_math::prelude::V3;
+
 const HEIGHT: u32 = 500;
 const WIDTH: u32 = 1000;
 
@@ -20,12 +22,16 @@ fn main() {
     );
     unsafe { CAMERA_ID = instance.register_object(default_objects::camera(true)) };
 
+    let mut light = default_objects::directional_light();
+    light.component_mut::<Transform>().rotation = V3::new(0.0, 45.0, 30.0);
+    instance.register_object(light);
+
     let mut timer = std::time::Instant::now();
     let mut frames = 0;
     loop {
         handle_fps(&mut timer, &mut frames);
 
-        let poll: Vec<_> = instance.event_pump.poll_iter().collect();
+        let poll = instance.poll_events();
 
         for event in poll {
             handle_input_event(event, &mut instance);
@@ -70,7 +76,7 @@ fn handle_input_event(event: Event, instance: &mut Instance) {
             xrel,
             yrel,
         } => {
-            cam_transform.rotation.z += INCREASE_ROTATION * xrel as f32;
+            cam_transform.rotation.z -= INCREASE_ROTATION * xrel as f32;
             cam_transform.rotation.y += INCREASE_ROTATION * yrel as f32;
         }
         Event::KeyDown {
@@ -91,16 +97,16 @@ fn handle_input_event(event: Event, instance: &mut Instance) {
                     cam_transform.position -= cam_transform.fwd() * INCREASE_POSITION;
                 }
                 Keycode::A => {
-                    cam_transform.position += cam_transform.right() * INCREASE_POSITION;
+                    cam_transform.position -= cam_transform.right() * INCREASE_POSITION;
                 }
                 Keycode::D => {
-                    cam_transform.position -= cam_transform.right() * INCREASE_POSITION;
+                    cam_transform.position += cam_transform.right() * INCREASE_POSITION;
                 }
                 Keycode::Left => {
-                    cam_transform.rotation.z -= INCREASE_ROTATION_KEY;
+                    cam_transform.rotation.z += INCREASE_ROTATION_KEY;
                 }
                 Keycode::Right => {
-                    cam_transform.rotation.z += INCREASE_ROTATION_KEY;
+                    cam_transform.rotation.z -= INCREASE_ROTATION_KEY;
                 }
                 Keycode::Up => {
                     cam_transform.rotation.y -= INCREASE_ROTATION_KEY;
@@ -12,7 +12,7 @@ fn main() {
         Instance::init(WIDTH, HEIGHT, BUFF_WIDTH, BUFF_HEIGHT).expect("Could not init rndr");
 
     loop {
-        for event in instance.event_pump.poll_iter() {
+        for event in instance.poll_events() {
             if let Event::Quit { timestamp: _ } = event {
                 panic!("Exit requested");
             }
@@ -4,15 +4,16 @@ use rndr_core::default_objects;
 use rndr_core::events::{Event, Keycode};
 use rndr_core::prelude::Instance;
 
-use rndr_core::render::FragShader;
+use rndr_core::render::{FragShader, ShaderParams, Uniforms};
 
 #[derive(Debug)]
 pub struct ZShader;
 
 impl FragShader for ZShader {
-    fn frag(&self, data: &mut rndr_core::prelude::FragData) {
-        let color = (255.0 * data.output_pixel().0) as u8;
-        data.output_pixel_mut().1 = [color, color, color]
+    fn frag(&self, data: &mut rndr_core::prelude::FragData, uniforms: &Uniforms) {
+        let depth_scale = uniforms.params.float("depth_scale").unwrap_or(1.0);
+        let color = (255.0 * data.view_depth() * depth_scale) as u8;
+        data.set_color([color, color, color]);
     }
 }
 
@@ -31,7 +32,9 @@ fn main() {
     let mut mesh_obj = default_objects::mesh_from_file("../../Utah_teapot_(solid).stl")
         .expect("Could not load mesh");
 
-    mesh_obj.component_mut::<MeshRenderable>().shader = Box::new(ZShader);
+    let mesh = mesh_obj.component_mut::<MeshRenderable>();
+    mesh.shader = Box::new(ZShader);
+    mesh.shader_params = ShaderParams::new().with("depth_scale", 0.05);
 
     instance.register_object(mesh_obj);
     unsafe { CAMERA_ID = instance.register_object(default_objects::camera(true)) };
@@ -41,12 +44,22 @@ fn main() {
     loop {
         handle_fps(&mut timer, &mut frames);
 
-        let poll: Vec<_> = instance.event_pump.poll_iter().collect();
+        let poll = instance.poll_events();
 
         for event in poll {
             handle_input_event(event, &mut instance);
         }
         instance.render().expect("Could not render");
+
+        if unsafe { SAVE_DEPTH } {
+            instance
+                .get_pixel_grid()
+                .save_depth("depth.png")
+                .expect("Could not save depth map");
+            println!("Depth map saved to depth.png");
+            unsafe { SAVE_DEPTH = false };
+        }
+
         instance.apply_render().expect("Could not apply render");
         frames += 1;
     }
@@ -61,6 +74,7 @@ fn handle_fps(timer: &mut std::time::Instant, frames: &mut i32) {
 }
 
 static mut CAMERA_ID: u64 = 0;
+static mut SAVE_DEPTH: bool = false;
 
 fn handle_input_event(event: Event, instance: &mut Instance) {
     const INCREASE_ROTATION: f32 = 0.08;
@@ -85,7 +99,7 @@ fn handle_input_event(event: Event, instance: &mut Instance) {
             xrel,
             yrel,
         } => {
-            cam_transform.rotation.z += INCREASE_ROTATION * xrel as f32;
+            cam_transform.rotation.z -= INCREASE_ROTATION * xrel as f32;
             cam_transform.rotation.y += INCREASE_ROTATION * yrel as f32;
         }
         Event::KeyDown {
@@ -93,6 +107,7 @@ fn handle_input_event(event: Event, instance: &mut Instance) {
             ..
         } => {
             match keycode {
+                Keycode::P => unsafe { SAVE_DEPTH = true },
                 Keycode::E => {
                     cam_transform.position += cam_transform.up() * INCREASE_POSITION;
                 }
@@ -106,16 +121,16 @@ fn handle_input_event(event: Event, instance: &mut Instance) {
                     cam_transform.position -= cam_transform.fwd() * INCREASE_POSITION;
                 }
                 Keycode::A => {
-                    cam_transform.position += cam_transform.right() * INCREASE_POSITION;
+                    cam_transform.position -= cam_transform.right() * INCREASE_POSITION;
                 }
                 Keycode::D => {
-                    cam_transform.position -= cam_transform.right() * INCREASE_POSITION;
+                    cam_transform.position += cam_transform.right() * INCREASE_POSITION;
                 }
                 Keycode::Left => {
-                    cam_transform.rotation.z -= INCREASE_ROTATION_KEY;
+                    cam_transform.rotation.z += INCREASE_ROTATION_KEY;
                 }
                 Keycode::Right => {
-                    cam_transform.rotation.z += INCREASE_ROTATION_KEY;
+                    cam_transform.rotation.z -= INCREASE_ROTATION_KEY;
                 }
                 Keycode::Up => {
                     cam_transform.rotation.y -= INCREASE_ROTATION_KEY;
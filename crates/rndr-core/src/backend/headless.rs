@@ -0,0 +1,29 @@
+use crate::instance::RenderApplyError;
+use crate::render::PixelGrid;
+
+use super::PresentationBackend;
+
+/// Backend that doesn't display anything, it only keeps a copy of the last presented frame
+/// so that it can be read back.
+pub struct HeadlessBackend {
+    frame: PixelGrid,
+}
+
+impl HeadlessBackend {
+    pub fn new(buff_width: u32, buff_height: u32) -> HeadlessBackend {
+        HeadlessBackend {
+            frame: PixelGrid::new(buff_width, buff_height),
+        }
+    }
+}
+
+impl PresentationBackend for HeadlessBackend {
+    fn present(&mut self, pixel_grid: &PixelGrid) -> Result<(), RenderApplyError> {
+        self.frame.clone_from(pixel_grid);
+        Ok(())
+    }
+
+    fn presented_frame(&self) -> Option<&PixelGrid> {
+        Some(&self.frame)
+    }
+}
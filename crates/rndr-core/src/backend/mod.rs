@@ -0,0 +1,21 @@
+pub mod headless;
+#[cfg(feature = "sdl")]
+pub mod sdl;
+
+pub use headless::HeadlessBackend;
+#[cfg(feature = "sdl")]
+pub use sdl::SdlBackend;
+
+use crate::instance::RenderApplyError;
+use crate::render::PixelGrid;
+
+/// Destination of the frames rendered by an `Instance`
+pub trait PresentationBackend {
+    /// Gets called by `Instance::apply_render` with the finished frame, right before the pixel grid is cleared
+    fn present(&mut self, pixel_grid: &PixelGrid) -> Result<(), RenderApplyError>;
+
+    /// The last frame passed to `present`, if the backend keeps a copy of it
+    fn presented_frame(&self) -> Option<&PixelGrid> {
+        None
+    }
+}
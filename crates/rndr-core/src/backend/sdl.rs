@@ -0,0 +1,72 @@
+use sdl2::render::{Texture, WindowCanvas};
+
+use crate::events::EventPump;
+use crate::instance::{InitError, RenderApplyError};
+use crate::render::PixelGrid;
+
+use super::PresentationBackend;
+
+/// Backend that streams the frames into a texture and presents it in an SDL window
+pub struct SdlBackend {
+    canvas: WindowCanvas,
+    buff_texture: Texture,
+}
+
+impl SdlBackend {
+    /// Opens a window of `width`x`height` that displays a buffer of `buff_width`x`buff_height`.
+    /// The event pump of the window is returned alongside the backend.
+    pub fn new(
+        width: u32,
+        height: u32,
+        buff_width: u32,
+        buff_height: u32,
+    ) -> Result<(SdlBackend, EventPump), InitError> {
+        let sdl_ctx = sdl2::init().map_err(InitError::SdlInit)?;
+        let video = sdl_ctx.video().map_err(InitError::SdlVideoInit)?;
+        let window = video
+            .window("Test", width, height)
+            .position_centered()
+            .input_grabbed()
+            .build()?;
+
+        let canvas = window
+            .into_canvas()
+            .build()
+            .map_err(InitError::SdlCanvasInit)?;
+
+        let mouse = sdl_ctx.mouse();
+        mouse.set_relative_mouse_mode(true);
+
+        let event_pump = sdl_ctx.event_pump().map_err(InitError::SdlEventPumpInit)?;
+        let buff_texture = canvas.create_texture_streaming(
+            sdl2::pixels::PixelFormatEnum::RGB24,
+            buff_width,
+            buff_height,
+        )?;
+
+        Ok((
+            SdlBackend {
+                canvas,
+                buff_texture,
+            },
+            event_pump,
+        ))
+    }
+}
+
+impl PresentationBackend for SdlBackend {
+    fn present(&mut self, pixel_grid: &PixelGrid) -> Result<(), RenderApplyError> {
+        self.buff_texture.update(
+            None,
+            pixel_grid.get_pixel_data(),
+            (pixel_grid.width * 3) as usize,
+        )?;
+
+        self.canvas
+            .copy(&self.buff_texture, None, None)
+            .map_err(RenderApplyError::SdlCanvasCopy)?;
+        self.canvas.present();
+
+        Ok(())
+    }
+}
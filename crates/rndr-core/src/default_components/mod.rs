@@ -1,4 +1,6 @@
 pub mod render;
+pub mod scene_node;
 pub mod transform;
 
+pub use scene_node::*;
 pub use transform::*;
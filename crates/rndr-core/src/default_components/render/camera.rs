@@ -1,27 +1,84 @@
 use std::any::TypeId;
+use std::sync::Arc;
 
-use rndr_math::prelude::{M3x3, Vertex, V3};
+use rndr_math::prelude::{M4x4, Vertex, V3};
 
 use crate::default_components::Transform;
 use crate::object::Component;
+use crate::render::{PixelRect, RenderTarget};
 
 #[derive(Debug)]
 pub struct Camera {
-    /// Wether the camera should use perspective projection
+    /// Wether the camera should use perspective projection, orthographic otherwise
     pub perspective: bool,
 
-    /// Displacement of the display surface relative to the camera pinhole.
-    /// None if not using perspective
-    pub display_surface_offset: Option<V3>,
+    /// Vertical field of view in degrees, used with perspective projection
+    pub fov: f32,
 
-    /// Projected screen points with z smaller than this value won't be shown
+    /// Half of the visible height in world units, used with orthographic projection
+    pub ortho_size: f32,
+
+    /// Width over height of the image. None to use the one of the pixels rendered into, so that the image
+    /// is never stretched.
+    pub aspect: Option<f32>,
+
+    /// Geometry closer to the camera than this value is clipped away
     pub near_plane: f32,
 
-    /// If |z| < `this value` where z is the projected pixel's z,
-    /// projection won't be applied to that pixel. This is done in order to
-    /// prevent extremely small z values from being used in projection calculations,
-    /// outputing lines millions of pixels long and affecting performance.
-    pub zero_threshold: f32,
+    /// Geometry further from the camera than this value is clipped away
+    pub far_plane: f32,
+
+    /// Part of the frame the camera renders into. Cameras without one are only rendered when they are
+    /// the main camera, into the whole frame. Cameras with one are rendered every frame, after the main camera.
+    /// With a `target`, the part of the target instead.
+    pub viewport: Option<Viewport>,
+
+    /// Cameras with a viewport are rendered from the lowest order to the highest, so that the last ones
+    /// end up on top. Ties are broken by registration order. Same for cameras with a target.
+    pub render_order: i32,
+
+    /// Renders into this target instead of the frame, every frame before the cameras of the frame so that
+    /// they see its latest image. Such a camera can't be the main camera.
+    pub target: Option<Arc<RenderTarget>>,
+}
+
+/// Rectangle of the frame, in fractions of its size from its top left corner
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Default for Viewport {
+    fn default() -> Viewport {
+        Viewport::new(0.0, 0.0, 1.0, 1.0)
+    }
+}
+
+impl Viewport {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Viewport {
+        Viewport {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Pixels covered by the viewport in a `width`x`height` frame. Edges are rounded to the closest
+    /// pixel, so that viewports sharing an edge neither overlap nor leave a gap.
+    pub fn pixels(&self, width: u32, height: u32) -> PixelRect {
+        let to_pixels =
+            |v: f32, size: u32| (v * size as f32).round().clamp(0.0, size as f32) as u32;
+
+        let (x, y) = (to_pixels(self.x, width), to_pixels(self.y, height));
+        let right = to_pixels(self.x + self.width, width).max(x);
+        let bottom = to_pixels(self.y + self.height, height).max(y);
+
+        PixelRect::new(x, y, right - x, bottom - y)
+    }
 }
 
 impl Component for Camera {
@@ -34,45 +91,154 @@ impl Camera {
     pub fn new(perspective: bool) -> Camera {
         Camera {
             perspective,
-            display_surface_offset: if perspective {
-                Some(V3::new(0.0, 0.0, -100.0))
-            } else {
-                None
-            },
+            fov: 60.0,
+            ortho_size: 5.0,
+            aspect: None,
             near_plane: 0.1,
-            zero_threshold: 0.01,
+            far_plane: 1000.0,
+            viewport: None,
+            render_order: 0,
+            target: None,
         }
     }
 
-    pub fn get_projection_matrix(&self, camera_transform: &Transform) -> M3x3 {
+    /// Aspect ratio of the image when rendering into a `width`x`height` rectangle of pixels
+    pub fn aspect_ratio(&self, width: u32, height: u32) -> f32 {
+        self.aspect.unwrap_or(width as f32 / height.max(1) as f32)
+    }
+
+    /// Transforms world space into camera space, where x is to the right, y up and z forward
+    pub fn view_matrix(&self, camera_transform: &Transform) -> M4x4 {
         let (fwd, right, up) = camera_transform.get_orientations_in_bulk();
+        let position = camera_transform.position;
 
-        M3x3::new([
-            V3::new(right.x, up.x, fwd.x),
-            V3::new(right.y, up.y, fwd.y),
-            V3::new(right.z, up.z, fwd.z),
-        ])
+        let row = |axis: V3| [axis.x, axis.y, axis.z, -axis.dot(position)];
+        M4x4::new([row(right), row(up), row(fwd), [0.0, 0.0, 0.0, 1.0]])
     }
 
-    pub fn project_point(
+    /// Transforms camera space into normalized device coordinates, see `M4x4::perspective`
+    pub fn projection_matrix(&self, aspect: f32) -> M4x4 {
+        if self.perspective {
+            M4x4::perspective(self.fov, aspect, self.near_plane, self.far_plane)
+        } else {
+            M4x4::orthographic(self.ortho_size, aspect, self.near_plane, self.far_plane)
+        }
+    }
+
+    /// Position of the vertex relative to the camera, see `view_matrix`
+    pub fn to_camera_space(
         &self,
-        projection_matrix: M3x3,
+        view_matrix: &M4x4,
         mut vertex: Vertex,
         object_transform: &Transform,
-        camera_transform: &Transform,
     ) -> V3 {
         object_transform.apply_to_vertex(&mut vertex);
+        view_matrix.transform_point(vertex.position)
+    }
+
+    /// Projects a point in camera space to normalized device coordinates: x and y are between -1 and 1 across
+    /// the image, from left to right and bottom to top, and z between 0 at the near plane and 1 at the far one
+    pub fn project(&self, projection_matrix: &M4x4, point: V3) -> V3 {
+        projection_matrix.project_point(point)
+    }
+
+    /// Pixel a point in world space lands on when rendering into a `width`x`height` buffer, from its top left
+    /// corner. z is the depth of the point in camera space. None if the point is closer than the near plane or
+    /// further than the far one.
+    pub fn project_point(
+        &self,
+        point: V3,
+        camera_transform: &Transform,
+        width: u32,
+        height: u32,
+    ) -> Option<V3> {
+        let camera_space = self.view_matrix(camera_transform).transform_point(point);
+        if camera_space.z < self.near_plane || camera_space.z > self.far_plane {
+            return None;
+        }
+
+        let projection_matrix = self.projection_matrix(self.aspect_ratio(width, height));
+        let ndc = self.project(&projection_matrix, camera_space);
+
+        Some(V3::new(
+            (ndc.x + 1.0) / 2.0 * width as f32,
+            (1.0 - ndc.y) / 2.0 * height as f32,
+            camera_space.z,
+        ))
+    }
+
+    /// Volume seen by the camera when rendering into a `width`x`height` buffer
+    pub fn frustum(&self, camera_transform: &Transform, width: u32, height: u32) -> Frustum {
+        let (fwd, right, up) = camera_transform.get_orientations_in_bulk();
+        let aspect = self.aspect_ratio(width, height);
 
-        vertex.position -= camera_transform.position;
+        // Planes are built in camera space, with normals pointing inside
+        let to_world_plane = |normal: V3, point: V3| {
+            let normal = (right * normal.x + up * normal.y + fwd * normal.z).norm();
+            let point = camera_transform.position + right * point.x + up * point.y + fwd * point.z;
+            Plane {
+                normal,
+                distance: -normal.dot(point),
+            }
+        };
 
-        let mut px = projection_matrix * vertex.position;
+        let near = to_world_plane(V3::new(0.0, 0.0, 1.0), V3::new(0.0, 0.0, self.near_plane));
+        let far = to_world_plane(V3::new(0.0, 0.0, -1.0), V3::new(0.0, 0.0, self.far_plane));
 
-        if self.perspective && px.z > 0.0 && px.z > self.zero_threshold {
-            let display_surface_offset = self.display_surface_offset.unwrap();
-            px.x = display_surface_offset.z / px.z * px.x + display_surface_offset.x;
-            px.y = display_surface_offset.z / px.z * px.y + display_surface_offset.y;
+        let sides = if self.perspective {
+            // Visible points have |y| / z up to the tangent of half the field of view, and |x| / z up to
+            // that times the aspect ratio
+            let half_height = (self.fov.to_radians() / 2.0).tan();
+            let half_width = half_height * aspect;
+
+            [
+                to_world_plane(V3::new(1.0, 0.0, half_width), V3::default()),
+                to_world_plane(V3::new(-1.0, 0.0, half_width), V3::default()),
+                to_world_plane(V3::new(0.0, 1.0, half_height), V3::default()),
+                to_world_plane(V3::new(0.0, -1.0, half_height), V3::default()),
+            ]
+        } else {
+            let half_height = self.ortho_size;
+            let half_width = half_height * aspect;
+
+            [
+                to_world_plane(V3::new(1.0, 0.0, 0.0), V3::new(-half_width, 0.0, 0.0)),
+                to_world_plane(V3::new(-1.0, 0.0, 0.0), V3::new(half_width, 0.0, 0.0)),
+                to_world_plane(V3::new(0.0, 1.0, 0.0), V3::new(0.0, -half_height, 0.0)),
+                to_world_plane(V3::new(0.0, -1.0, 0.0), V3::new(0.0, half_height, 0.0)),
+            ]
+        };
+
+        Frustum {
+            planes: [near, far, sides[0], sides[1], sides[2], sides[3]],
         }
+    }
+}
+
+/// Plane in world space, points with a positive distance are on the side `normal` points to
+#[derive(Debug, Clone, Copy)]
+pub struct Plane {
+    pub normal: V3,
+    pub distance: f32,
+}
+
+impl Plane {
+    pub fn signed_distance(&self, point: V3) -> f32 {
+        self.normal.dot(point) + self.distance
+    }
+}
+
+/// Planes bounding the volume visible by a camera
+#[derive(Debug, Clone, Copy)]
+pub struct Frustum {
+    pub planes: [Plane; 6],
+}
 
-        px
+impl Frustum {
+    /// Whether any part of the sphere could be visible
+    pub fn intersects_sphere(&self, center: V3, radius: f32) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.signed_distance(center) >= -radius)
     }
 }
@@ -0,0 +1,184 @@
+use std::any::TypeId;
+
+use rndr_math::prelude::V3;
+
+use crate::default_components::Transform;
+use crate::object::Component;
+
+/// Light coming from infinitely far away, like the sun. It shines towards the `fwd` of its `Transform`.
+#[derive(Debug, Clone, Copy)]
+pub struct DirectionalLight {
+    pub color: [u8; 3],
+    pub intensity: f32,
+}
+
+/// Light shining in every direction from the position of its `Transform`
+#[derive(Debug, Clone, Copy)]
+pub struct PointLight {
+    pub color: [u8; 3],
+    pub intensity: f32,
+    /// Distance at which the light stops having any effect
+    pub range: f32,
+}
+
+/// Light shining in a cone from the position of its `Transform`, towards its `fwd`
+#[derive(Debug, Clone, Copy)]
+pub struct SpotLight {
+    pub color: [u8; 3],
+    pub intensity: f32,
+    /// Distance at which the light stops having any effect
+    pub range: f32,
+    /// Angle in degrees between the direction of the light and the edge of the cone
+    pub outer_angle: f32,
+    /// Angle in degrees up to which the light has full intensity, it fades out from there to `outer_angle`
+    pub inner_angle: f32,
+}
+
+impl DirectionalLight {
+    pub fn new(color: [u8; 3], intensity: f32) -> DirectionalLight {
+        DirectionalLight { color, intensity }
+    }
+}
+
+impl PointLight {
+    pub fn new(color: [u8; 3], intensity: f32, range: f32) -> PointLight {
+        PointLight {
+            color,
+            intensity,
+            range,
+        }
+    }
+}
+
+impl SpotLight {
+    pub fn new(color: [u8; 3], intensity: f32, range: f32, outer_angle: f32) -> SpotLight {
+        SpotLight {
+            color,
+            intensity,
+            range,
+            outer_angle,
+            inner_angle: outer_angle * 0.8,
+        }
+    }
+}
+
+impl Component for DirectionalLight {
+    fn get_type(&self) -> TypeId {
+        TypeId::of::<DirectionalLight>()
+    }
+}
+
+impl Component for PointLight {
+    fn get_type(&self) -> TypeId {
+        TypeId::of::<PointLight>()
+    }
+}
+
+impl Component for SpotLight {
+    fn get_type(&self) -> TypeId {
+        TypeId::of::<SpotLight>()
+    }
+}
+
+/// A light of the scene, resolved to world space so that shaders can use it
+#[derive(Debug, Clone, Copy)]
+pub enum Light {
+    Directional {
+        /// Direction the light travels in
+        direction: V3,
+        color: V3,
+    },
+    Point {
+        position: V3,
+        color: V3,
+        range: f32,
+    },
+    Spot {
+        position: V3,
+        direction: V3,
+        color: V3,
+        range: f32,
+        /// Cosine of the outer angle
+        outer_cos: f32,
+        /// Cosine of the inner angle
+        inner_cos: f32,
+    },
+}
+
+/// Color scaled by intensity, with each channel between 0 and 1 at intensity 1
+fn radiance(color: [u8; 3], intensity: f32) -> V3 {
+    V3::new(color[0] as f32, color[1] as f32, color[2] as f32) * (intensity / 255.0)
+}
+
+impl Light {
+    pub fn directional(light: &DirectionalLight, transform: &Transform) -> Light {
+        Light::Directional {
+            direction: transform.fwd().norm(),
+            color: radiance(light.color, light.intensity),
+        }
+    }
+
+    pub fn point(light: &PointLight, transform: &Transform) -> Light {
+        Light::Point {
+            position: transform.position,
+            color: radiance(light.color, light.intensity),
+            range: light.range,
+        }
+    }
+
+    pub fn spot(light: &SpotLight, transform: &Transform) -> Light {
+        Light::Spot {
+            position: transform.position,
+            direction: transform.fwd().norm(),
+            color: radiance(light.color, light.intensity),
+            range: light.range,
+            outer_cos: light.outer_angle.to_radians().cos(),
+            inner_cos: light.inner_angle.to_radians().cos(),
+        }
+    }
+
+    /// Direction from `point` towards the light and the light's color once it reaches `point`.
+    /// None if the light doesn't reach it.
+    pub fn incidence(&self, point: V3) -> Option<(V3, V3)> {
+        match *self {
+            Light::Directional { direction, color } => Some((-direction, color)),
+            Light::Point {
+                position,
+                color,
+                range,
+            } => {
+                let (to_light, attenuation) = attenuate(position - point, range)?;
+                Some((to_light, color * attenuation))
+            }
+            Light::Spot {
+                position,
+                direction,
+                color,
+                range,
+                outer_cos,
+                inner_cos,
+            } => {
+                let (to_light, attenuation) = attenuate(position - point, range)?;
+
+                let cos = (-to_light).dot(direction);
+                if cos <= outer_cos {
+                    return None;
+                }
+                let cone = ((cos - outer_cos) / (inner_cos - outer_cos).max(f32::EPSILON)).min(1.0);
+
+                Some((to_light, color * (attenuation * cone * cone)))
+            }
+        }
+    }
+}
+
+/// Normalized direction and attenuation of a light at `offset` with the given range
+fn attenuate(offset: V3, range: f32) -> Option<(V3, f32)> {
+    let distance = offset.mag();
+    if distance >= range {
+        return None;
+    }
+
+    let falloff = 1.0 - distance / range;
+    Some((offset.norm(), falloff * falloff))
+}
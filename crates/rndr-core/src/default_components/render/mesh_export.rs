@@ -0,0 +1,256 @@
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use rndr_math::prelude::{Vertex, V3};
+use thiserror::Error;
+
+use super::MeshRenderable;
+use crate::default_components::Transform;
+
+#[derive(Error, Debug)]
+pub enum MeshExportError {
+    #[error("Could not infer mesh format from path: {0}")]
+    UnknownFormat(PathBuf),
+    #[error("Could not write file: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeshFormat {
+    /// Positions, texture coordinates and normals. Materials aren't written
+    Obj,
+    /// Binary STL, only has positions and a flat normal per triangle
+    Stl,
+    /// Same as `Stl`, as text
+    StlAscii,
+    /// ASCII PLY, with every attribute of the vertices including their colors
+    Ply,
+}
+
+impl MeshFormat {
+    /// `.stl` files are written as binary, see `MeshRenderable::save_with_format` for ASCII ones
+    pub fn from_path(path: &Path) -> Option<MeshFormat> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "obj" => Some(MeshFormat::Obj),
+            "stl" => Some(MeshFormat::Stl),
+            "ply" => Some(MeshFormat::Ply),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            MeshFormat::Obj => "obj",
+            MeshFormat::Stl | MeshFormat::StlAscii => "stl",
+            MeshFormat::Ply => "ply",
+        }
+    }
+}
+
+impl MeshRenderable {
+    /// Writes the vertices and triangles to `path`, the format is inferred from the extension
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), MeshExportError> {
+        self.save_with_transform(path.as_ref(), None)
+    }
+
+    /// Same as `save`, in `format` whatever the extension of `path`
+    pub fn save_with_format(
+        &self,
+        path: impl AsRef<Path>,
+        format: MeshFormat,
+    ) -> Result<(), MeshExportError> {
+        self.write_file(path.as_ref(), format, None)
+    }
+
+    /// Same as `save`, with `transform` applied to the vertices the same way as when rendering,
+    /// so that the file is in world space
+    pub fn save_transformed(
+        &self,
+        path: impl AsRef<Path>,
+        transform: &Transform,
+    ) -> Result<(), MeshExportError> {
+        self.save_with_transform(path.as_ref(), Some(transform))
+    }
+
+    /// Writes the mesh in the given format, with `transform` applied to it if any
+    pub fn write(
+        &self,
+        writer: &mut impl Write,
+        format: MeshFormat,
+        transform: Option<&Transform>,
+    ) -> Result<(), MeshExportError> {
+        let vertices = match transform {
+            Some(transform) => Cow::Owned(
+                self.vertices
+                    .iter()
+                    .map(|vertex| {
+                        let mut vertex = *vertex;
+                        transform.apply_to_vertex(&mut vertex);
+                        vertex.normal = vertex.normal.rotate(transform.rotation);
+                        vertex
+                    })
+                    .collect(),
+            ),
+            None => Cow::Borrowed(&self.vertices),
+        };
+
+        match format {
+            MeshFormat::Obj => write_obj(writer, &vertices, &self.triangles)?,
+            MeshFormat::Stl => write_stl(writer, &vertices, &self.triangles)?,
+            MeshFormat::StlAscii => write_stl_ascii(writer, &vertices, &self.triangles)?,
+            MeshFormat::Ply => write_ply(writer, &vertices, &self.triangles)?,
+        }
+
+        Ok(())
+    }
+
+    fn save_with_transform(
+        &self,
+        path: &Path,
+        transform: Option<&Transform>,
+    ) -> Result<(), MeshExportError> {
+        let format = MeshFormat::from_path(path)
+            .ok_or_else(|| MeshExportError::UnknownFormat(path.to_path_buf()))?;
+        self.write_file(path, format, transform)
+    }
+
+    fn write_file(
+        &self,
+        path: &Path,
+        format: MeshFormat,
+        transform: Option<&Transform>,
+    ) -> Result<(), MeshExportError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer, format, transform)?;
+        writer.flush()?;
+
+        Ok(())
+    }
+}
+
+fn write_obj(
+    writer: &mut impl Write,
+    vertices: &[Vertex],
+    triangles: &[[usize; 3]],
+) -> std::io::Result<()> {
+    for v in vertices.iter() {
+        let p = v.position;
+        writeln!(writer, "v {} {} {}", p.x, p.y, p.z)?;
+    }
+    for v in vertices.iter() {
+        writeln!(writer, "vt {} {}", v.uv.x, v.uv.y)?;
+    }
+    for v in vertices.iter() {
+        writeln!(writer, "vn {} {} {}", v.normal.x, v.normal.y, v.normal.z)?;
+    }
+
+    // Every vertex has one of each, so they share their index, which starts at 1
+    for triangle in triangles.iter() {
+        let [a, b, c] = triangle.map(|i| i + 1);
+        writeln!(writer, "f {a}/{a}/{a} {b}/{b}/{b} {c}/{c}/{c}")?;
+    }
+
+    Ok(())
+}
+
+fn write_stl(
+    writer: &mut impl Write,
+    vertices: &[Vertex],
+    triangles: &[[usize; 3]],
+) -> std::io::Result<()> {
+    let to_array = |v: V3| [v.x, v.y, v.z];
+
+    let triangles: Vec<stl::Triangle> = triangles
+        .iter()
+        .map(|triangle| {
+            let [a, b, c] = triangle.map(|i| vertices[i].position);
+            stl::Triangle {
+                normal: to_array(face_normal(a, b, c)),
+                v1: to_array(a),
+                v2: to_array(b),
+                v3: to_array(c),
+                attr_byte_count: 0,
+            }
+        })
+        .collect();
+
+    let file = stl::BinaryStlFile {
+        header: stl::BinaryStlHeader {
+            header: [0; 80],
+            num_triangles: triangles.len() as u32,
+        },
+        triangles,
+    };
+
+    stl::write_stl(writer, &file)
+}
+
+fn write_stl_ascii(
+    writer: &mut impl Write,
+    vertices: &[Vertex],
+    triangles: &[[usize; 3]],
+) -> std::io::Result<()> {
+    writeln!(writer, "solid rndr")?;
+
+    for triangle in triangles.iter() {
+        let [a, b, c] = triangle.map(|i| vertices[i].position);
+        let n = face_normal(a, b, c);
+
+        writeln!(writer, "facet normal {} {} {}", n.x, n.y, n.z)?;
+        writeln!(writer, "  outer loop")?;
+        for p in [a, b, c] {
+            writeln!(writer, "    vertex {} {} {}", p.x, p.y, p.z)?;
+        }
+        writeln!(writer, "  endloop")?;
+        writeln!(writer, "endfacet")?;
+    }
+
+    writeln!(writer, "endsolid rndr")
+}
+
+fn write_ply(
+    writer: &mut impl Write,
+    vertices: &[Vertex],
+    triangles: &[[usize; 3]],
+) -> std::io::Result<()> {
+    writeln!(writer, "ply")?;
+    writeln!(writer, "format ascii 1.0")?;
+    writeln!(writer, "element vertex {}", vertices.len())?;
+    for property in ["x", "y", "z", "nx", "ny", "nz", "s", "t"] {
+        writeln!(writer, "property float {property}")?;
+    }
+    for property in ["red", "green", "blue"] {
+        writeln!(writer, "property uchar {property}")?;
+    }
+    writeln!(writer, "element face {}", triangles.len())?;
+    writeln!(writer, "property list uchar uint vertex_indices")?;
+    writeln!(writer, "end_header")?;
+
+    for v in vertices.iter() {
+        let (p, n) = (v.position, v.normal);
+        let [r, g, b] = v.color;
+        writeln!(
+            writer,
+            "{} {} {} {} {} {} {} {} {r} {g} {b}",
+            p.x, p.y, p.z, n.x, n.y, n.z, v.uv.x, v.uv.y
+        )?;
+    }
+    for [a, b, c] in triangles.iter() {
+        writeln!(writer, "3 {a} {b} {c}")?;
+    }
+
+    Ok(())
+}
+
+/// Normal of a counter clockwise triangle, zero if it is degenerate
+fn face_normal(a: V3, b: V3, c: V3) -> V3 {
+    let normal = (b - a).cross(c - a);
+    if normal.mag() > 0.0 {
+        normal.norm()
+    } else {
+        V3::default()
+    }
+}
@@ -0,0 +1,622 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::ops::{Add, Mul};
+
+use rndr_math::prelude::{Vertex, V3};
+
+use super::MeshRenderable;
+
+/// How much more moving away from a border costs than moving away from a surface
+const BORDER_WEIGHT: f32 = 100.0;
+
+/// Sine of the angle below which a triangle counts as degenerate, it has no area to get a normal from
+const DEGENERATE_SINE: f32 = 1e-6;
+
+/// Normal of the vertices that only touch degenerate triangles
+const FALLBACK_NORMAL: V3 = V3 {
+    x: 0.0,
+    y: 0.0,
+    z: 1.0,
+};
+
+/// Operations rebuilding the vertices and triangles of a mesh, which all update its bounds.
+///
+/// Vertices at the same position but with different attributes, like the ones on both sides of a
+/// texture seam, are treated as the same point of the surface so that the mesh doesn't open up there.
+impl MeshRenderable {
+    /// Scales the vertices along the axes of the mesh. A scale mirroring the mesh, with an odd amount of
+    /// negative axes, also reverses the triangles so that they keep facing outwards.
+    pub fn scale(&mut self, scale: V3) {
+        for vertex in self.vertices.iter_mut() {
+            vertex.position = vertex.position.hadamard_product(scale);
+            // Normals stay perpendicular to the surface when scaled by the inverse
+            vertex.normal = vertex.normal.hadamard_product(scale.inverse()).norm();
+        }
+
+        if scale.x * scale.y * scale.z < 0.0 {
+            for triangle in self.triangles.iter_mut() {
+                triangle.swap(1, 2);
+            }
+        }
+
+        self.recalculate_bounds();
+    }
+
+    /// Replaces the normals by the ones of the triangles around each vertex, only averaging triangles
+    /// making an angle of at most `smoothing_angle` degrees with each other. 0 gives flat normals and
+    /// 180 smooth ones everywhere. Vertices are split where they end up with more than one normal.
+    ///
+    /// Degenerate triangles are left out of the normals. Their vertices get the smooth normal of the
+    /// triangles around them, or `FALLBACK_NORMAL` if there are none.
+    pub fn recalculate_normals(&mut self, smoothing_angle: f32) {
+        let min_cos = smoothing_angle.clamp(0.0, 180.0).to_radians().cos() - 1e-4;
+        let points = Points::new(&self.vertices);
+
+        // Not normalized, so that bigger triangles weigh more
+        let face_normals: Vec<Option<V3>> = self
+            .triangles
+            .iter()
+            .map(|triangle| {
+                let [a, b, c] = triangle.map(|i| self.vertices[i].position);
+                let normal = (b - a).cross(c - a);
+                (normal.mag() > DEGENERATE_SINE * (b - a).mag() * (c - a).mag()).then_some(normal)
+            })
+            .collect();
+
+        let mut faces_around = vec![Vec::new(); points.positions.len()];
+        for (face, triangle) in self.triangles.iter().enumerate() {
+            if face_normals[face].is_none() {
+                continue;
+            }
+            for &index in triangle.iter() {
+                faces_around[points.ids[index]].push(face);
+            }
+        }
+
+        let mut vertices = Vec::with_capacity(self.vertices.len());
+        let mut split: HashMap<(usize, [u32; 3]), usize> = HashMap::new();
+
+        for (face, triangle) in self.triangles.iter_mut().enumerate() {
+            let own = face_normals[face].map(|normal| normal.norm());
+
+            for index in triangle.iter_mut() {
+                let sum = faces_around[points.ids[*index]]
+                    .iter()
+                    .filter_map(|&other| face_normals[other])
+                    .filter(|other| own.is_none_or(|own| other.norm().dot(own) >= min_cos))
+                    .fold(V3::default(), |sum, other| sum + other);
+                let normal = if sum.mag() > 0.0 {
+                    sum.norm()
+                } else {
+                    FALLBACK_NORMAL
+                };
+
+                *index = *split
+                    .entry((*index, position_key(normal)))
+                    .or_insert_with(|| {
+                        vertices.push(Vertex {
+                            normal,
+                            ..self.vertices[*index]
+                        });
+                        vertices.len() - 1
+                    });
+            }
+        }
+
+        self.vertices = vertices;
+        self.recalculate_bounds();
+    }
+
+    /// Merges vertices closer than `distance` to each other into the first of them, removing the triangles
+    /// that collapse. Merged vertices keep the attributes of that first one, texture seams included.
+    pub fn weld_vertices(&mut self, distance: f32) {
+        let cell_size = distance.max(f32::EPSILON);
+        let cell = |p: V3| [p.x, p.y, p.z].map(|c| (c / cell_size).floor() as i64);
+
+        // Vertices kept so far by the cell they are in, any vertex to merge is in the cells around
+        let mut grid: HashMap<[i64; 3], Vec<usize>> = HashMap::new();
+        let mut vertices: Vec<Vertex> = Vec::new();
+        let mut remap = Vec::with_capacity(self.vertices.len());
+
+        for vertex in self.vertices.iter() {
+            let [x, y, z] = cell(vertex.position);
+
+            let merged_into = (-1..=1)
+                .flat_map(|dx| (-1..=1).flat_map(move |dy| (-1..=1).map(move |dz| (dx, dy, dz))))
+                .filter_map(|(dx, dy, dz)| grid.get(&[x + dx, y + dy, z + dz]))
+                .flatten()
+                .copied()
+                .find(|&kept| (vertices[kept].position - vertex.position).mag() <= distance);
+
+            match merged_into {
+                Some(kept) => remap.push(kept),
+                None => {
+                    vertices.push(*vertex);
+                    grid.entry([x, y, z]).or_default().push(vertices.len() - 1);
+                    remap.push(vertices.len() - 1);
+                }
+            }
+        }
+
+        self.vertices = vertices;
+        self.triangles = self
+            .triangles
+            .iter()
+            .map(|triangle| triangle.map(|i| remap[i]))
+            .filter(|&[a, b, c]| a != b && b != c && c != a)
+            .collect();
+
+        self.remove_unused_vertices();
+        self.recalculate_bounds();
+    }
+
+    /// Loop subdivision, splitting every triangle in 4 `levels` times while smoothing the surface.
+    /// Attributes of the new vertices are interpolated, and borders of open meshes stay curves going
+    /// through their original vertices.
+    pub fn subdivide(&mut self, levels: u32) {
+        for _ in 0..levels {
+            self.subdivide_once();
+        }
+        self.recalculate_bounds();
+    }
+
+    /// Collapses edges until at most `target_triangles` are left, the ones changing the shape the least
+    /// first according to their quadric error. Borders are kept in place as much as possible, and
+    /// collapses that would flip a triangle are skipped, so the target may not always be reached.
+    /// Normals of the remaining vertices are kept, see `recalculate_normals`.
+    pub fn decimate(&mut self, target_triangles: usize) {
+        let points = Points::new(&self.vertices);
+        let mut positions = points.positions.clone();
+
+        // Triangles as points, collapsing an edge replaces one of its points by the other
+        let mut corners: Vec<[usize; 3]> = self
+            .triangles
+            .iter()
+            .map(|triangle| triangle.map(|i| points.ids[i]))
+            .collect();
+        let mut alive: Vec<bool> = corners
+            .iter()
+            .map(|&[a, b, c]| a != b && b != c && c != a)
+            .collect();
+        let mut remaining = alive.iter().filter(|&&alive| alive).count();
+
+        let mut faces_around = vec![Vec::new(); positions.len()];
+        let mut quadrics = vec![Quadric::default(); positions.len()];
+        let mut edge_faces: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+
+        for (face, &[a, b, c]) in corners.iter().enumerate() {
+            if !alive[face] {
+                continue;
+            }
+
+            let normal = (positions[b] - positions[a]).cross(positions[c] - positions[a]);
+            // Weighted by area, so that small triangles don't hold big ones in place
+            let quadric = Quadric::plane(normal.norm(), positions[a]) * (normal.mag() / 2.0);
+
+            for point in [a, b, c] {
+                faces_around[point].push(face);
+                quadrics[point] = quadrics[point] + quadric;
+            }
+            for (a, b) in [(a, b), (b, c), (c, a)] {
+                edge_faces.entry(edge_key(a, b)).or_default().push(face);
+            }
+        }
+
+        // Borders get planes perpendicular to their triangle, which are costly to move away from
+        for (&(a, b), faces) in edge_faces.iter() {
+            if let [face] = faces[..] {
+                let [p0, p1, p2] = corners[face].map(|i| positions[i]);
+                let face_normal = (p1 - p0).cross(p2 - p0);
+                let edge = positions[b] - positions[a];
+
+                let quadric = Quadric::plane(edge.cross(face_normal).norm(), positions[a])
+                    * (edge.dot(edge) * BORDER_WEIGHT);
+                quadrics[a] = quadrics[a] + quadric;
+                quadrics[b] = quadrics[b] + quadric;
+            }
+        }
+
+        // Each point is invalidated when it moves or is removed, along with the collapses queued for it
+        let mut versions = vec![0u32; positions.len()];
+        let mut merged_into: Vec<usize> = (0..positions.len()).collect();
+
+        let queue_collapse = |heap: &mut BinaryHeap<Collapse>,
+                              positions: &[V3],
+                              quadrics: &[Quadric],
+                              versions: &[u32],
+                              (a, b): (usize, usize)| {
+            let quadric = quadrics[a] + quadrics[b];
+            let (target, cost) = quadric.best_point(positions[a], positions[b]);
+            heap.push(Collapse {
+                cost,
+                points: (a, b),
+                versions: (versions[a], versions[b]),
+                target,
+            });
+        };
+
+        let mut heap = BinaryHeap::new();
+        let mut edges: Vec<(usize, usize)> = edge_faces.into_keys().collect();
+        edges.sort_unstable();
+        for edge in edges {
+            queue_collapse(&mut heap, &positions, &quadrics, &versions, edge);
+        }
+
+        while remaining > target_triangles {
+            let Some(collapse) = heap.pop() else {
+                break;
+            };
+            let (a, b) = collapse.points;
+            if collapse.versions != (versions[a], versions[b]) {
+                continue;
+            }
+
+            // Triangles that stay around the merged point must keep facing the same way
+            let flips = faces_around[a]
+                .iter()
+                .chain(faces_around[b].iter())
+                .filter(|&&face| alive[face])
+                .filter(|&&face| !(corners[face].contains(&a) && corners[face].contains(&b)))
+                .any(|&face| {
+                    let before = corners[face].map(|i| positions[i]);
+                    let after = corners[face].map(|i| {
+                        if i == a || i == b {
+                            collapse.target
+                        } else {
+                            positions[i]
+                        }
+                    });
+                    let normal = |[p0, p1, p2]: [V3; 3]| (p1 - p0).cross(p2 - p0);
+                    normal(before).dot(normal(after)) <= 0.0
+                });
+            if flips {
+                continue;
+            }
+
+            positions[a] = collapse.target;
+            quadrics[a] = quadrics[a] + quadrics[b];
+            versions[a] += 1;
+            versions[b] += 1;
+            merged_into[b] = a;
+
+            let moved = std::mem::take(&mut faces_around[b]);
+            for &face in moved.iter() {
+                if !alive[face] {
+                    continue;
+                }
+                for corner in corners[face].iter_mut() {
+                    if *corner == b {
+                        *corner = a;
+                    }
+                }
+                let [x, y, z] = corners[face];
+                if x == y || y == z || z == x {
+                    alive[face] = false;
+                    remaining -= 1;
+                }
+            }
+            faces_around[a].extend(moved);
+            faces_around[a].retain(|&face| alive[face]);
+            faces_around[a].sort_unstable();
+            faces_around[a].dedup();
+
+            let mut neighbours: Vec<usize> = faces_around[a]
+                .iter()
+                .flat_map(|&face| corners[face])
+                .filter(|&point| point != a)
+                .collect();
+            neighbours.sort_unstable();
+            neighbours.dedup();
+            for neighbour in neighbours {
+                queue_collapse(
+                    &mut heap,
+                    &positions,
+                    &quadrics,
+                    &versions,
+                    edge_key(a, neighbour),
+                );
+            }
+        }
+
+        // Vertices of merged points all move to where they ended up, which collapses the removed triangles
+        for (vertex, &id) in self.vertices.iter_mut().zip(points.ids.iter()) {
+            let mut id = id;
+            while merged_into[id] != id {
+                id = merged_into[id];
+            }
+            vertex.position = positions[id];
+        }
+
+        self.triangles = self
+            .triangles
+            .iter()
+            .zip(alive.iter())
+            .filter(|(_, &alive)| alive)
+            .map(|(&triangle, _)| triangle)
+            .collect();
+
+        self.remove_unused_vertices();
+        self.recalculate_bounds();
+    }
+
+    fn subdivide_once(&mut self) {
+        let points = Points::new(&self.vertices);
+        let p = &points.positions;
+
+        // Points opposite to each edge, edges with only one are on a border
+        let mut edges: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+        for triangle in self.triangles.iter() {
+            let [a, b, c] = triangle.map(|i| points.ids[i]);
+            for (a, b, opposite) in [(a, b, c), (b, c, a), (c, a, b)] {
+                edges.entry(edge_key(a, b)).or_default().push(opposite);
+            }
+        }
+
+        let mut neighbours = vec![Vec::new(); p.len()];
+        let mut border_neighbours = vec![Vec::new(); p.len()];
+        for (&(a, b), opposite) in edges.iter() {
+            neighbours[a].push(b);
+            neighbours[b].push(a);
+            if opposite.len() != 2 {
+                border_neighbours[a].push(b);
+                border_neighbours[b].push(a);
+            }
+        }
+        // Sums don't depend on the order of the map
+        for list in neighbours.iter_mut().chain(border_neighbours.iter_mut()) {
+            list.sort_unstable();
+        }
+
+        // Existing points move towards their neighbours, the ones on a border only along it
+        let smoothed: Vec<V3> = (0..p.len())
+            .map(|i| match border_neighbours[i][..] {
+                [] if !neighbours[i].is_empty() => {
+                    let n = neighbours[i].len() as f32;
+                    let beta = if neighbours[i].len() == 3 {
+                        3.0 / 16.0
+                    } else {
+                        3.0 / (8.0 * n)
+                    };
+                    let sum = neighbours[i]
+                        .iter()
+                        .fold(V3::default(), |sum, &j| sum + p[j]);
+                    p[i] * (1.0 - n * beta) + sum * beta
+                }
+                [a, b] => p[i] * 0.75 + (p[a] + p[b]) * 0.125,
+                // Corners where borders meet stay in place
+                _ => p[i],
+            })
+            .collect();
+
+        let edge_point = |a: usize, b: usize| match edges[&edge_key(a, b)][..] {
+            [c, d] => (p[a] + p[b]) * 0.375 + (p[c] + p[d]) * 0.125,
+            _ => p[a].average(p[b]),
+        };
+
+        let mut vertices: Vec<Vertex> = self
+            .vertices
+            .iter()
+            .zip(points.ids.iter())
+            .map(|(vertex, &id)| Vertex {
+                position: smoothed[id],
+                ..*vertex
+            })
+            .collect();
+
+        // By vertex rather than by point, so that both sides of a seam keep their own attributes
+        let mut midpoints: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut triangles = Vec::with_capacity(self.triangles.len() * 4);
+
+        for &[a, b, c] in self.triangles.iter() {
+            let mut midpoint = |i: usize, j: usize| {
+                *midpoints.entry(edge_key(i, j)).or_insert_with(|| {
+                    vertices.push(Vertex {
+                        position: edge_point(points.ids[i], points.ids[j]),
+                        ..interpolate(&self.vertices[i], &self.vertices[j])
+                    });
+                    vertices.len() - 1
+                })
+            };
+
+            let (ab, bc, ca) = (midpoint(a, b), midpoint(b, c), midpoint(c, a));
+            triangles.extend([[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]);
+        }
+
+        self.vertices = vertices;
+        self.triangles = triangles;
+    }
+
+    /// Removes the vertices no triangle uses, keeping the order of the others
+    fn remove_unused_vertices(&mut self) {
+        let mut used = vec![false; self.vertices.len()];
+        for &index in self.triangles.iter().flatten() {
+            used[index] = true;
+        }
+
+        let mut remap = vec![0; self.vertices.len()];
+        let mut vertices = Vec::with_capacity(self.vertices.len());
+        for (index, vertex) in self.vertices.iter().enumerate() {
+            if used[index] {
+                remap[index] = vertices.len();
+                vertices.push(*vertex);
+            }
+        }
+
+        self.vertices = vertices;
+        for index in self.triangles.iter_mut().flatten() {
+            *index = remap[*index];
+        }
+    }
+}
+
+/// Vertices grouped by position
+struct Points {
+    /// Point of each vertex
+    ids: Vec<usize>,
+    positions: Vec<V3>,
+}
+
+impl Points {
+    fn new(vertices: &[Vertex]) -> Points {
+        let mut by_position = HashMap::new();
+        let mut positions = Vec::new();
+
+        let ids = vertices
+            .iter()
+            .map(|vertex| {
+                *by_position
+                    .entry(position_key(vertex.position))
+                    .or_insert_with(|| {
+                        positions.push(vertex.position);
+                        positions.len() - 1
+                    })
+            })
+            .collect();
+
+        Points { ids, positions }
+    }
+}
+
+fn position_key(v: V3) -> [u32; 3] {
+    // -0 and 0 have different bits
+    [v.x + 0.0, v.y + 0.0, v.z + 0.0].map(f32::to_bits)
+}
+
+fn edge_key(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
+
+/// Vertex halfway between `a` and `b`
+fn interpolate(a: &Vertex, b: &Vertex) -> Vertex {
+    Vertex {
+        position: a.position.average(b.position),
+        normal: (a.normal + b.normal).norm(),
+        color: std::array::from_fn(|i| ((a.color[i] as u16 + b.color[i] as u16) / 2) as u8),
+        uv: (a.uv + b.uv) / 2.0,
+    }
+}
+
+/// Sum of the squared distances to a set of planes, as the symmetric 4x4 matrix of Garland and Heckbert
+#[derive(Debug, Default, Clone, Copy)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+    /// Plane going through `point` with the given normal
+    fn plane(normal: V3, point: V3) -> Quadric {
+        let [a, b, c] = [normal.x, normal.y, normal.z].map(|v| v as f64);
+        let d = -(normal.dot(point) as f64);
+        Quadric([
+            a * a,
+            a * b,
+            a * c,
+            a * d,
+            b * b,
+            b * c,
+            b * d,
+            c * c,
+            c * d,
+            d * d,
+        ])
+    }
+
+    fn error(&self, p: V3) -> f64 {
+        let q = &self.0;
+        let [x, y, z] = [p.x, p.y, p.z].map(|v| v as f64);
+        q[0] * x * x
+            + 2.0 * q[1] * x * y
+            + 2.0 * q[2] * x * z
+            + 2.0 * q[3] * x
+            + q[4] * y * y
+            + 2.0 * q[5] * y * z
+            + 2.0 * q[6] * y
+            + q[7] * z * z
+            + 2.0 * q[8] * z
+            + q[9]
+    }
+
+    /// Point with the smallest error for an edge from `a` to `b`, and that error. Flat and straight
+    /// areas have no single best point, the best of the ends and the middle of the edge is used instead.
+    fn best_point(&self, a: V3, b: V3) -> (V3, f64) {
+        let q = &self.0;
+        let m = [[q[0], q[1], q[2]], [q[1], q[4], q[5]], [q[2], q[5], q[7]]];
+        let rhs = [-q[3], -q[6], -q[8]];
+
+        let det = |m: [[f64; 3]; 3]| {
+            m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+                - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+                + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+        };
+        let determinant = det(m);
+        let scale = q[0] + q[4] + q[7];
+
+        if determinant.abs() > 1e-6 * scale * scale * scale {
+            // Cramer's rule
+            let [x, y, z] = std::array::from_fn(|column| {
+                let mut replaced = m;
+                for row in 0..3 {
+                    replaced[row][column] = rhs[row];
+                }
+                (det(replaced) / determinant) as f32
+            });
+            let point = V3::new(x, y, z);
+            // Nearly flat areas can put it far away from the edge
+            if (point - a.average(b)).mag() <= (b - a).mag() {
+                return (point, self.error(point));
+            }
+        }
+
+        [a, b, a.average(b)]
+            .into_iter()
+            .map(|point| (point, self.error(point)))
+            .min_by(|x, y| x.1.total_cmp(&y.1))
+            .unwrap()
+    }
+}
+
+impl Add for Quadric {
+    type Output = Quadric;
+
+    fn add(self, other: Quadric) -> Quadric {
+        Quadric(std::array::from_fn(|i| self.0[i] + other.0[i]))
+    }
+}
+
+impl Mul<f32> for Quadric {
+    type Output = Quadric;
+
+    fn mul(self, weight: f32) -> Quadric {
+        Quadric(self.0.map(|v| v * weight as f64))
+    }
+}
+
+/// Edge collapse waiting in the queue, ordered so that the cheapest one comes out first
+struct Collapse {
+    cost: f64,
+    points: (usize, usize),
+    /// Versions of the points when it was queued, it is outdated if either changed since
+    versions: (u32, u32),
+    target: V3,
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Collapse) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Collapse) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Collapse {
+    fn cmp(&self, other: &Collapse) -> Ordering {
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then_with(|| other.points.cmp(&self.points))
+    }
+}
@@ -1,37 +1,147 @@
 use std::any::TypeId;
 use std::fmt::Debug;
+use std::path::Path;
+use std::time::{Duration, Instant};
 
-use rndr_math::prelude::{Vertex, V3};
+use rndr_math::prelude::{Vertex, V2, V3};
+use russimp::mesh::Mesh;
 use russimp::scene::{PostProcess, Scene};
 use russimp::RussimpError;
+use thiserror::Error;
 
 use crate::default_components::Transform;
 use crate::object::Component;
-use crate::render::{shader::DefaultShader, FragShader};
+use crate::render::{
+    shader::DefaultShader, BlendMode, DepthFunc, FragShader, LitShader, Material, MaterialShader,
+    ShaderParams, TextureError, VertexShader,
+};
+
+#[derive(Error, Debug)]
+pub enum MeshLoadError {
+    #[error("Could not import mesh: {0}")]
+    Import(#[from] RussimpError),
+    #[error("Could not load texture of material: {0}")]
+    Texture(#[from] TextureError),
+    #[error("File has no meshes")]
+    NoMeshes,
+}
+
+/// Numbers about a loaded file
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LoadStats {
+    pub meshes: usize,
+    pub vertices: usize,
+    pub triangles: usize,
+    /// Faces that weren't triangles, which are skipped
+    pub skipped_faces: usize,
+    /// Time spent parsing the file
+    pub import_time: Duration,
+    /// Time spent converting the meshes and loading the textures of their materials
+    pub conversion_time: Duration,
+}
+
+/// Which triangles are skipped depending on whether they face the camera
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CullMode {
+    /// Every triangle is drawn, for double sided meshes
+    None,
+    /// Triangles facing away from the camera aren't drawn. Enough for closed meshes
+    #[default]
+    Back,
+    /// Triangles facing the camera aren't drawn
+    Front,
+}
 
 #[derive(Debug)]
 pub struct MeshRenderable {
     pub vertices: Vec<Vertex>,
     vertices_center: V3,
+    /// Distance from `vertices_center` to the furthest vertex
+    bounding_radius: f32,
+    /// Smallest and biggest coordinates of the vertices
+    bounds: (V3, V3),
     pub triangles: Vec<[usize; 3]>,
     pub shader: Box<dyn FragShader>,
+    /// Runs on the vertices before they are projected, if any
+    pub vertex_shader: Option<Box<dyn VertexShader>>,
+    /// How far the vertex shader can move vertices out of the mesh. Frustum culling grows the bounding sphere
+    /// by it. Objects with a vertex shader are never culled while it is `None`, since their bounds are unknown.
+    pub bounds_padding: Option<f32>,
+    /// Values passed to the shader through `Uniforms::params`
+    pub shader_params: ShaderParams,
+    /// Passed to the shader through `Uniforms::material`, imported meshes get the one of their file
+    pub material: Option<Material>,
+    pub cull_mode: CullMode,
+    /// How the depth of the fragments is compared against the depth buffer
+    pub depth_func: DepthFunc,
+    /// Objects with any other mode than `BlendMode::Opaque` are transparent. They are drawn after the opaque ones,
+    /// from the furthest to the closest, and left out of the depth prepass.
+    pub blend_mode: BlendMode,
+    /// Whether the fragments write their depth, so that they hide what is drawn behind them afterwards.
+    /// Usually off for transparent objects that overlap each other.
+    pub depth_write: bool,
 }
 
 impl MeshRenderable {
+    /// Mesh shaded by a `LitShader`, with its back faces culled. Triangles are counter clockwise when
+    /// seen from the outside of the mesh.
+    pub fn new(vertices: Vec<Vertex>, triangles: Vec<[usize; 3]>) -> MeshRenderable {
+        let mut ret = MeshRenderable {
+            vertices,
+            vertices_center: V3::default(),
+            bounding_radius: 0.0,
+            bounds: (V3::default(), V3::default()),
+            triangles,
+            shader: Box::new(LitShader::default()),
+            vertex_shader: None,
+            bounds_padding: None,
+            shader_params: ShaderParams::default(),
+            material: None,
+            cull_mode: CullMode::Back,
+            depth_func: DepthFunc::default(),
+            blend_mode: BlendMode::default(),
+            depth_write: true,
+        };
+        ret.recalculate_bounds();
+        ret
+    }
+
     pub fn plane() -> MeshRenderable {
         let n = V3::new(0.0, 1.0, 0.0);
         let mut ret = MeshRenderable {
             vertices: vec![
-                Vertex::new(V3::new(-1.0, 0.0, -1.0), [255; 3], n),
-                Vertex::new(V3::new(-1.0, 0.0, 1.0), [255; 3], n),
-                Vertex::new(V3::new(1.0, 0.0, 1.0), [255; 3], n),
-                Vertex::new(V3::new(1.0, 0.0, -1.0), [255; 3], n),
+                Vertex {
+                    uv: V2::new(0.0, 0.0),
+                    ..Vertex::new(V3::new(-1.0, 0.0, -1.0), [255; 3], n)
+                },
+                Vertex {
+                    uv: V2::new(0.0, 1.0),
+                    ..Vertex::new(V3::new(-1.0, 0.0, 1.0), [255; 3], n)
+                },
+                Vertex {
+                    uv: V2::new(1.0, 1.0),
+                    ..Vertex::new(V3::new(1.0, 0.0, 1.0), [255; 3], n)
+                },
+                Vertex {
+                    uv: V2::new(1.0, 0.0),
+                    ..Vertex::new(V3::new(1.0, 0.0, -1.0), [255; 3], n)
+                },
             ],
             vertices_center: V3::default(),
+            bounding_radius: 0.0,
+            bounds: (V3::default(), V3::default()),
             triangles: vec![[0, 1, 2], [0, 2, 3]],
             shader: Box::from(DefaultShader),
+            vertex_shader: None,
+            bounds_padding: None,
+            shader_params: ShaderParams::default(),
+            material: None,
+            cull_mode: CullMode::None,
+            depth_func: DepthFunc::default(),
+            blend_mode: BlendMode::default(),
+            depth_write: true,
         };
-        ret.vertices_center = Self::find_vertex_average(&ret.vertices);
+        ret.recalculate_bounds();
         ret
     }
     pub fn small_plane() -> MeshRenderable {
@@ -43,67 +153,173 @@ impl MeshRenderable {
                 Vertex::new_with_color(V3::new(0.2, 0.0, -0.2), [255; 3]),
             ],
             vertices_center: V3::default(),
+            bounding_radius: 0.0,
+            bounds: (V3::default(), V3::default()),
             triangles: vec![[0, 1, 2], [0, 2, 3]],
             shader: Box::from(DefaultShader),
+            vertex_shader: None,
+            bounds_padding: None,
+            shader_params: ShaderParams::default(),
+            material: None,
+            cull_mode: CullMode::None,
+            depth_func: DepthFunc::default(),
+            blend_mode: BlendMode::default(),
+            depth_write: true,
         };
-        ret.vertices_center = Self::find_vertex_average(&ret.vertices);
+        ret.recalculate_bounds();
         ret
     }
 
-    pub fn from_file(path: &str) -> Result<MeshRenderable, RussimpError> {
-        let mesh = &Scene::from_file(
+    /// Loads the first mesh of a file along with its material, shaded by a `MaterialShader`.
+    /// See `default_objects::scene_from_file` to load every mesh of a file.
+    pub fn from_file(path: &str) -> Result<MeshRenderable, MeshLoadError> {
+        Ok(Self::from_file_with_stats(path)?.0)
+    }
+
+    /// Same as `from_file`, also returns numbers about the loading
+    pub fn from_file_with_stats(path: &str) -> Result<(MeshRenderable, LoadStats), MeshLoadError> {
+        let mut stats = LoadStats::default();
+
+        let start = Instant::now();
+        let scene = Self::import_scene(path)?;
+        stats.import_time = start.elapsed();
+
+        let start = Instant::now();
+        let mesh = scene.meshes.first().ok_or(MeshLoadError::NoMeshes)?;
+
+        // Textures are referenced relative to the file
+        let directory = Path::new(path).parent().unwrap_or(Path::new(""));
+        let material = scene
+            .materials
+            .get(mesh.material_index as usize)
+            .map(|material| Material::from_russimp(material, directory))
+            .transpose()?;
+
+        let renderable = Self::from_russimp(mesh, material, &mut stats);
+        stats.conversion_time = start.elapsed();
+
+        Ok((renderable, stats))
+    }
+
+    pub(crate) fn import_scene(path: &str) -> Result<Scene, RussimpError> {
+        Scene::from_file(
             path,
             vec![
                 PostProcess::JoinIdenticalVertices,
                 PostProcess::GenerateNormals,
                 PostProcess::Triangulate,
             ],
-        )?
-        .meshes[0];
+        )
+    }
+
+    /// Copies the vertices of `mesh` as they are, so that the indices of its faces stay valid.
+    /// Faces that aren't triangles, points and lines left by triangulation, are skipped.
+    pub(crate) fn from_russimp(
+        mesh: &Mesh,
+        material: Option<Material>,
+        stats: &mut LoadStats,
+    ) -> MeshRenderable {
+        // Only the first channel of each is used, meshes without colors are white
+        let colors = mesh.colors.first().and_then(|c| c.as_ref());
+        let uvs = mesh.texture_coords.first().and_then(|uv| uv.as_ref());
+
+        let vertices: Vec<Vertex> = mesh
+            .vertices
+            .iter()
+            .enumerate()
+            .map(|(i, p)| Vertex {
+                position: V3::new(p.x, p.y, p.z),
+                normal: mesh
+                    .normals
+                    .get(i)
+                    .map(|n| V3::new(n.x, n.y, n.z))
+                    .unwrap_or_default(),
+                color: colors
+                    .map(|colors| {
+                        let c = colors[i];
+                        [c.r, c.g, c.b].map(|c| (c.clamp(0.0, 1.0) * 255.0) as u8)
+                    })
+                    .unwrap_or([255; 3]),
+                uv: uvs
+                    .map(|uvs| V2::new(uvs[i].x, uvs[i].y))
+                    .unwrap_or_default(),
+            })
+            .collect();
+
+        // Allocated once for every face, as filtering them hides the final size from `collect`
+        let mut triangles: Vec<[usize; 3]> = Vec::with_capacity(mesh.faces.len());
+        triangles.extend(mesh.faces.iter().filter_map(|face| match face.0[..] {
+            [a, b, c] => Some([a as usize, b as usize, c as usize]),
+            _ => None,
+        }));
+
+        stats.meshes += 1;
+        stats.vertices += vertices.len();
+        stats.triangles += triangles.len();
+        stats.skipped_faces += mesh.faces.len() - triangles.len();
+
+        let translucent = material
+            .as_ref()
+            .is_some_and(|material| material.opacity < 1.0);
 
         let mut object = MeshRenderable {
-            vertices: Vec::default(),
-            triangles: Vec::default(),
+            vertices,
+            triangles,
             vertices_center: V3::default(),
-            shader: Box::new(DefaultShader),
+            bounding_radius: 0.0,
+            bounds: (V3::default(), V3::default()),
+            shader: Box::new(MaterialShader::default()),
+            vertex_shader: None,
+            bounds_padding: None,
+            shader_params: ShaderParams::default(),
+            material,
+            cull_mode: CullMode::Back,
+            depth_func: DepthFunc::default(),
+            blend_mode: if translucent {
+                BlendMode::Alpha
+            } else {
+                BlendMode::Opaque
+            },
+            depth_write: true,
         };
+        object.recalculate_bounds();
+
+        object
+    }
+
+    /// Updates the cached center and bounding volumes, needs to be called after modifying `vertices`
+    pub fn recalculate_bounds(&mut self) {
+        self.vertices_center = Self::find_vertex_average(&self.vertices);
+        (self.bounding_radius, self.bounds) =
+            Self::find_bounds(&self.vertices, self.vertices_center);
+    }
 
-        // In meshes that reuse vertices for multiple faces we need to check if some vertices haven't been pushed in yet
-        let mut pushed_vertices = Vec::new();
-
-        for face in mesh.faces.iter() {
-            object
-                .triangles
-                .push([face.0[0] as usize, face.0[1] as usize, face.0[2] as usize]);
-
-            for i in 0..face.0.len() {
-                let idx = face.0[i] as usize;
-                if pushed_vertices.iter().find(|v| **v == idx).is_some() {
-                    continue;
-                }
-                pushed_vertices.push(idx);
-                let p = mesh.vertices[idx];
-                let p = V3::new(p.x, p.y, p.z);
-                let n = mesh.normals[idx];
-                let n = V3::new(n.x, n.y, n.z);
-                let v = Vertex {
-                    position: V3::new(p.x, p.y, p.z),
-                    normal: V3::new(n.x, n.y, n.z),
-                    color: if i == 0 {
-                        [255, 0, 0]
-                    } else if i == 1 {
-                        [0, 255, 0]
-                    } else {
-                        [0, 0, 255]
-                    },
-                };
-                object.vertices.push(v);
-            }
+    /// Center and radius of a sphere containing the whole mesh once `transform` is applied
+    pub fn bounding_sphere(&self, transform: &Transform) -> (V3, f32) {
+        (
+            self.vertices_center.rotate(transform.rotation) + transform.position,
+            self.bounding_radius,
+        )
+    }
+
+    /// Sphere used to frustum cull the object, `bounding_sphere` grown by `bounds_padding`.
+    /// None if the object can't be culled, see `bounds_padding`.
+    pub fn culling_sphere(&self, transform: &Transform) -> Option<(V3, f32)> {
+        if self.vertex_shader.is_some() && self.bounds_padding.is_none() {
+            return None;
         }
+        let (center, radius) = self.bounding_sphere(transform);
+        Some((center, radius + self.bounds_padding.unwrap_or(0.0)))
+    }
 
-        object.vertices_center = Self::find_vertex_average(&object.vertices);
+    /// Axis aligned bounding box of the vertices, before any transform is applied
+    pub fn local_bounds(&self) -> (V3, V3) {
+        self.bounds
+    }
 
-        Ok(object)
+    /// Whether the object is blended with what is behind it, see `blend_mode`
+    pub fn is_transparent(&self) -> bool {
+        self.blend_mode != BlendMode::Opaque
     }
 
     pub fn calculate_center(&self, transform: &Transform) -> V3 {
@@ -131,6 +347,22 @@ impl MeshRenderable {
 
         V3::new(center_x, center_y, center_z)
     }
+
+    fn find_bounds(vertices: &[Vertex], center: V3) -> (f32, (V3, V3)) {
+        let mut radius: f32 = 0.0;
+        let mut min = V3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY);
+        let mut max = V3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY);
+
+        for vertex in vertices.iter() {
+            let p = vertex.position;
+            radius = radius.max((p - center).mag());
+
+            min = V3::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z));
+            max = V3::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z));
+        }
+
+        (radius, (min, max))
+    }
 }
 
 impl Component for MeshRenderable {
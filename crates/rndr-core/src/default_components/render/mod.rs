@@ -1,5 +1,11 @@
 pub mod camera;
+pub mod light;
+pub mod mesh_export;
+mod mesh_ops;
 pub mod mesh_renderable;
+mod primitives;
 
 pub use camera::*;
+pub use light::*;
+pub use mesh_export::*;
 pub use mesh_renderable::*;
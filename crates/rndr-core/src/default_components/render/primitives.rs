@@ -0,0 +1,358 @@
+use std::collections::HashMap;
+use std::f32::consts::{PI, TAU};
+
+use rndr_math::prelude::{Vertex, V2, V3};
+
+use super::MeshRenderable;
+
+/// Procedural meshes, all centered on the origin with their axis along Z and white vertices.
+///
+/// Curved surfaces have smooth normals. Texture coordinates wrap around curved surfaces once,
+/// with a seam of duplicated vertices where they go back from 1 to 0.
+impl MeshRenderable {
+    /// Cube of `size` per side, each face mapped to the whole texture
+    pub fn cube(size: f32) -> MeshRenderable {
+        let half = size / 2.0;
+        let axes = [
+            V3::new(1.0, 0.0, 0.0),
+            V3::new(0.0, 1.0, 0.0),
+            V3::new(0.0, 0.0, 1.0),
+        ];
+
+        let mut vertices = Vec::with_capacity(24);
+        let mut triangles = Vec::with_capacity(12);
+
+        for i in 0..3 {
+            for sign in [1.0, -1.0] {
+                let normal = axes[i] * sign;
+                // Two axes along the face, such that u x v points outwards
+                let u = axes[(i + 1) % 3] * sign;
+                let v = axes[(i + 2) % 3];
+
+                let first = vertices.len();
+                for (x, y) in [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)] {
+                    let position = (normal + u * (x * 2.0 - 1.0) + v * (y * 2.0 - 1.0)) * half;
+                    vertices.push(vertex(position, normal, V2::new(x, y)));
+                }
+                push_quad(&mut triangles, first, first + 1, first + 2, first + 3);
+            }
+        }
+
+        MeshRenderable::new(vertices, triangles)
+    }
+
+    /// Sphere made of `segments` slices around Z and `rings` stacked along it.
+    /// U goes around the sphere and V from the bottom pole to the top one.
+    pub fn uv_sphere(radius: f32, segments: u32, rings: u32) -> MeshRenderable {
+        let (segments, rings) = (segments.max(3), rings.max(2));
+
+        let mut vertices = Vec::with_capacity(((segments + 1) * (rings + 1)) as usize);
+        for ring in 0..=rings {
+            let v = ring as f32 / rings as f32;
+            let polar = v * PI;
+
+            for segment in 0..=segments {
+                let u = segment as f32 / segments as f32;
+                let azimuth = u * TAU;
+
+                let normal = V3::new(
+                    polar.sin() * azimuth.cos(),
+                    polar.sin() * azimuth.sin(),
+                    -polar.cos(),
+                );
+                vertices.push(vertex(normal * radius, normal, V2::new(u, v)));
+            }
+        }
+
+        let mut triangles = Vec::new();
+        let row = segments as usize + 1;
+        for ring in 0..rings as usize {
+            for segment in 0..segments as usize {
+                let a = ring * row + segment;
+                let (b, c, d) = (a + 1, a + row, a + row + 1);
+
+                // The triangles touching a pole would be degenerate
+                if ring != 0 {
+                    triangles.push([a, b, d]);
+                }
+                if ring != rings as usize - 1 {
+                    triangles.push([a, d, c]);
+                }
+            }
+        }
+
+        MeshRenderable::new(vertices, triangles)
+    }
+
+    /// Sphere made of triangles of about the same size, by splitting each face of an icosahedron
+    /// in 4 `subdivisions` times. Texture coordinates are the same as the ones of `uv_sphere`.
+    pub fn icosphere(radius: f32, subdivisions: u32) -> MeshRenderable {
+        let phi = (1.0 + 5f32.sqrt()) / 2.0;
+        let mut points: Vec<V3> = [
+            (-1.0, phi, 0.0),
+            (1.0, phi, 0.0),
+            (-1.0, -phi, 0.0),
+            (1.0, -phi, 0.0),
+            (0.0, -1.0, phi),
+            (0.0, 1.0, phi),
+            (0.0, -1.0, -phi),
+            (0.0, 1.0, -phi),
+            (phi, 0.0, -1.0),
+            (phi, 0.0, 1.0),
+            (-phi, 0.0, -1.0),
+            (-phi, 0.0, 1.0),
+        ]
+        .iter()
+        .map(|&(x, y, z)| V3::new(x, y, z).norm())
+        .collect();
+
+        let mut faces: Vec<[usize; 3]> = vec![
+            [0, 11, 5],
+            [0, 5, 1],
+            [0, 1, 7],
+            [0, 7, 10],
+            [0, 10, 11],
+            [1, 5, 9],
+            [5, 11, 4],
+            [11, 10, 2],
+            [10, 7, 6],
+            [7, 1, 8],
+            [3, 9, 4],
+            [3, 4, 2],
+            [3, 2, 6],
+            [3, 6, 8],
+            [3, 8, 9],
+            [4, 9, 5],
+            [2, 4, 11],
+            [6, 2, 10],
+            [8, 6, 7],
+            [9, 8, 1],
+        ];
+
+        for _ in 0..subdivisions {
+            // Edges are shared by two faces, which have to share the point in their middle as well
+            let mut midpoints: HashMap<(usize, usize), usize> = HashMap::new();
+            let mut midpoint = |a: usize, b: usize, points: &mut Vec<V3>| {
+                *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                    points.push(points[a].average(points[b]).norm());
+                    points.len() - 1
+                })
+            };
+
+            faces = faces
+                .iter()
+                .flat_map(|&[a, b, c]| {
+                    let ab = midpoint(a, b, &mut points);
+                    let bc = midpoint(b, c, &mut points);
+                    let ca = midpoint(c, a, &mut points);
+                    [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+                })
+                .collect();
+        }
+
+        let mut vertices: Vec<Vertex> = points
+            .iter()
+            .map(|&normal| {
+                let u = (normal.y.atan2(normal.x) / TAU).rem_euclid(1.0);
+                let v = normal.z.clamp(-1.0, 1.0).asin() / PI + 0.5;
+                vertex(normal * radius, normal, V2::new(u, v))
+            })
+            .collect();
+
+        // The poles have no direction around Z, they are left out of the seam and placed afterwards
+        let is_pole =
+            |vertex: &Vertex| vertex.normal.x.abs() < 1e-6 && vertex.normal.y.abs() < 1e-6;
+
+        // Faces crossing the seam would go through the whole texture backwards,
+        // their vertices on the 0 side of it get a copy on the 1 side
+        let mut seam_copies: HashMap<usize, usize> = HashMap::new();
+        for face in faces.iter_mut() {
+            let us: Vec<f32> = face
+                .iter()
+                .filter(|&&i| !is_pole(&vertices[i]))
+                .map(|&i| vertices[i].uv.x)
+                .collect();
+            let max = us.iter().copied().fold(f32::MIN, f32::max);
+            let min = us.iter().copied().fold(f32::MAX, f32::min);
+            if max - min < 0.5 {
+                continue;
+            }
+
+            for index in face.iter_mut() {
+                if vertices[*index].uv.x < 0.5 && !is_pole(&vertices[*index]) {
+                    *index = *seam_copies.entry(*index).or_insert_with(|| {
+                        let mut copy = vertices[*index];
+                        copy.uv.x += 1.0;
+                        vertices.push(copy);
+                        vertices.len() - 1
+                    });
+                }
+            }
+        }
+
+        // Each face touching a pole gets its own copy of it, in the middle of its two other vertices
+        for face in faces.iter_mut() {
+            for corner in 0..3 {
+                let mut pole = vertices[face[corner]];
+                if !is_pole(&pole) {
+                    continue;
+                }
+
+                let (b, c) = (face[(corner + 1) % 3], face[(corner + 2) % 3]);
+                pole.uv.x = (vertices[b].uv.x + vertices[c].uv.x) / 2.0;
+                vertices.push(pole);
+                face[corner] = vertices.len() - 1;
+            }
+        }
+
+        MeshRenderable::new(vertices, faces)
+    }
+
+    /// Cylinder of `height` along Z, with its sides made of `segments` quads and closed by two caps.
+    /// U goes around the sides and V from the bottom to the top, the caps are mapped from above.
+    pub fn cylinder(radius: f32, height: f32, segments: u32) -> MeshRenderable {
+        let segments = segments.max(3);
+        let half = height / 2.0;
+
+        let mut vertices = Vec::new();
+        let mut triangles = Vec::new();
+
+        for segment in 0..=segments {
+            let u = segment as f32 / segments as f32;
+            let normal = around(u);
+            let position = normal * radius;
+
+            vertices.push(vertex(position - up() * half, normal, V2::new(u, 0.0)));
+            vertices.push(vertex(position + up() * half, normal, V2::new(u, 1.0)));
+        }
+        for segment in 0..segments as usize {
+            let a = segment * 2;
+            push_quad(&mut triangles, a, a + 2, a + 3, a + 1);
+        }
+
+        push_cap(&mut vertices, &mut triangles, radius, -half, segments);
+        push_cap(&mut vertices, &mut triangles, radius, half, segments);
+
+        MeshRenderable::new(vertices, triangles)
+    }
+
+    /// Cone of `height` along Z with its tip at the top, its side made of `segments` triangles
+    /// and closed by a cap at the bottom. Mapped the same way as `cylinder`.
+    pub fn cone(radius: f32, height: f32, segments: u32) -> MeshRenderable {
+        let segments = segments.max(3);
+        let half = height / 2.0;
+
+        // The side leans in by the slope of the cone, so do its normals
+        let side_normal = |u: f32| (around(u) * height + up() * radius).norm();
+
+        let mut vertices = Vec::new();
+        let mut triangles = Vec::new();
+
+        for segment in 0..=segments {
+            let u = segment as f32 / segments as f32;
+            let position = around(u) * radius - up() * half;
+            vertices.push(vertex(position, side_normal(u), V2::new(u, 0.0)));
+        }
+        // Each triangle gets its own tip, with the normal of the middle of the triangle,
+        // otherwise the tip would be shaded as flat
+        for segment in 0..segments as usize {
+            let u = (segment as f32 + 0.5) / segments as f32;
+            vertices.push(vertex(up() * half, side_normal(u), V2::new(u, 1.0)));
+            triangles.push([segment, segment + 1, vertices.len() - 1]);
+        }
+
+        push_cap(&mut vertices, &mut triangles, radius, -half, segments);
+
+        MeshRenderable::new(vertices, triangles)
+    }
+
+    /// Torus around Z, of `major_radius` from its center to the middle of the tube and `minor_radius`
+    /// for the tube. U goes around Z and V around the tube, starting from the outside.
+    pub fn torus(
+        major_radius: f32,
+        minor_radius: f32,
+        major_segments: u32,
+        minor_segments: u32,
+    ) -> MeshRenderable {
+        let (major_segments, minor_segments) = (major_segments.max(3), minor_segments.max(3));
+
+        let mut vertices = Vec::new();
+        for major in 0..=major_segments {
+            let u = major as f32 / major_segments as f32;
+            let outwards = around(u);
+
+            for minor in 0..=minor_segments {
+                let v = minor as f32 / minor_segments as f32;
+                let angle = v * TAU;
+
+                let normal = outwards * angle.cos() + up() * angle.sin();
+                let position = outwards * major_radius + normal * minor_radius;
+                vertices.push(vertex(position, normal, V2::new(u, v)));
+            }
+        }
+
+        let mut triangles = Vec::new();
+        let row = minor_segments as usize + 1;
+        for major in 0..major_segments as usize {
+            for minor in 0..minor_segments as usize {
+                let a = major * row + minor;
+                push_quad(&mut triangles, a, a + row, a + row + 1, a + 1);
+            }
+        }
+
+        MeshRenderable::new(vertices, triangles)
+    }
+}
+
+fn vertex(position: V3, normal: V3, uv: V2) -> Vertex {
+    Vertex {
+        uv,
+        ..Vertex::new(position, [255; 3], normal)
+    }
+}
+
+fn up() -> V3 {
+    V3::new(0.0, 0.0, 1.0)
+}
+
+/// Direction at `u` turns counter clockwise around Z, starting from X
+fn around(u: f32) -> V3 {
+    let angle = u * TAU;
+    V3::new(angle.cos(), angle.sin(), 0.0)
+}
+
+/// Two triangles of a quad whose corners are counter clockwise
+fn push_quad(triangles: &mut Vec<[usize; 3]>, a: usize, b: usize, c: usize, d: usize) {
+    triangles.push([a, b, c]);
+    triangles.push([a, c, d]);
+}
+
+/// Disk at height `z` facing up if `z` is positive and down otherwise
+fn push_cap(
+    vertices: &mut Vec<Vertex>,
+    triangles: &mut Vec<[usize; 3]>,
+    radius: f32,
+    z: f32,
+    segments: u32,
+) {
+    let normal = if z > 0.0 { up() } else { -up() };
+    let uv = |direction: V3| V2::new(direction.x * 0.5 + 0.5, direction.y * 0.5 + 0.5);
+
+    let center = vertices.len();
+    vertices.push(vertex(up() * z, normal, V2::new(0.5, 0.5)));
+
+    for segment in 0..segments {
+        let direction = around(segment as f32 / segments as f32);
+        vertices.push(vertex(direction * radius + up() * z, normal, uv(direction)));
+    }
+
+    for segment in 0..segments as usize {
+        let current = center + 1 + segment;
+        let next = center + 1 + (segment + 1) % segments as usize;
+        if z > 0.0 {
+            triangles.push([center, current, next]);
+        } else {
+            triangles.push([center, next, current]);
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use std::any::TypeId;
+
+use crate::object::Component;
+
+/// Place of an object in the hierarchy of the file it was imported from.
+///
+/// Only records the relationships, the `Transform` of every object is already in world space
+/// and moving a parent doesn't move its children.
+#[derive(Debug, Default, Clone)]
+pub struct SceneNode {
+    pub name: String,
+    /// Id of the parent object, None for the root of the file
+    pub parent: Option<u64>,
+    /// Ids of the child objects
+    pub children: Vec<u64>,
+}
+
+impl SceneNode {
+    pub fn new(name: impl Into<String>, parent: Option<u64>) -> SceneNode {
+        SceneNode {
+            name: name.into(),
+            parent,
+            children: Vec::new(),
+        }
+    }
+}
+
+impl Component for SceneNode {
+    fn get_type(&self) -> TypeId {
+        TypeId::of::<SceneNode>()
+    }
+}
@@ -1,6 +1,10 @@
-use russimp::RussimpError;
+mod scene;
 
-use crate::default_components::render::{Camera, MeshRenderable};
+pub use scene::{scene_from_file, scene_from_file_with_stats};
+
+use crate::default_components::render::{
+    Camera, DirectionalLight, MeshLoadError, MeshRenderable, PointLight, SpotLight,
+};
 use crate::default_components::Transform;
 use crate::object::Object;
 
@@ -11,7 +15,29 @@ pub fn camera(perspective: bool) -> Object {
     camera
 }
 
-pub fn mesh_from_file(path: &str) -> Result<Object, RussimpError> {
+/// White light shining towards the `fwd` of its transform
+pub fn directional_light() -> Object {
+    let mut light = Object::new();
+    light.add_component(Box::new(Transform::default()));
+    light.add_component(Box::new(DirectionalLight::new([255; 3], 1.0)));
+    light
+}
+
+pub fn point_light(range: f32) -> Object {
+    let mut light = Object::new();
+    light.add_component(Box::new(Transform::default()));
+    light.add_component(Box::new(PointLight::new([255; 3], 1.0, range)));
+    light
+}
+
+pub fn spot_light(range: f32, angle: f32) -> Object {
+    let mut light = Object::new();
+    light.add_component(Box::new(Transform::default()));
+    light.add_component(Box::new(SpotLight::new([255; 3], 1.0, range, angle)));
+    light
+}
+
+pub fn mesh_from_file(path: &str) -> Result<Object, MeshLoadError> {
     let mut object = Object::new();
     object.add_component(Box::new(MeshRenderable::from_file(path)?));
     object.add_component(Box::new(Transform::default()));
@@ -33,3 +59,44 @@ pub fn small_plane() -> Object {
 
     object
 }
+
+fn mesh_object(mesh: MeshRenderable) -> Object {
+    let mut object = Object::new();
+    object.add_component(Box::new(mesh));
+    object.add_component(Box::new(Transform::default()));
+    object
+}
+
+pub fn cube(size: f32) -> Object {
+    mesh_object(MeshRenderable::cube(size))
+}
+
+pub fn uv_sphere(radius: f32, segments: u32, rings: u32) -> Object {
+    mesh_object(MeshRenderable::uv_sphere(radius, segments, rings))
+}
+
+pub fn icosphere(radius: f32, subdivisions: u32) -> Object {
+    mesh_object(MeshRenderable::icosphere(radius, subdivisions))
+}
+
+pub fn cylinder(radius: f32, height: f32, segments: u32) -> Object {
+    mesh_object(MeshRenderable::cylinder(radius, height, segments))
+}
+
+pub fn cone(radius: f32, height: f32, segments: u32) -> Object {
+    mesh_object(MeshRenderable::cone(radius, height, segments))
+}
+
+pub fn torus(
+    major_radius: f32,
+    minor_radius: f32,
+    major_segments: u32,
+    minor_segments: u32,
+) -> Object {
+    mesh_object(MeshRenderable::torus(
+        major_radius,
+        minor_radius,
+        major_segments,
+        minor_segments,
+    ))
+}
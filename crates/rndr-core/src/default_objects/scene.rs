@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Instant;
+
+use rndr_math::prelude::{M4x4, V3};
+use russimp::node::Node;
+use russimp::scene::Scene;
+use russimp::Matrix4x4;
+
+use crate::default_components::render::{LoadStats, MeshLoadError, MeshRenderable};
+use crate::default_components::{SceneNode, Transform};
+use crate::instance::Instance;
+use crate::object::Object;
+use crate::render::Material;
+
+/// Imports every node of a file as an object with a `SceneNode`, registers them and returns their ids,
+/// parents always coming before their children.
+///
+/// Nodes with a single mesh get it as their `MeshRenderable`, each mesh of a node with more than one
+/// becomes a child object of its own. Transforms are the world transforms of the nodes. As `Transform`
+/// can't scale, the scale of a node is applied to the vertices of its meshes, see `MeshRenderable::scale`.
+///
+/// Nothing is registered if the import fails.
+pub fn scene_from_file(instance: &mut Instance, path: &str) -> Result<Vec<u64>, MeshLoadError> {
+    Ok(scene_from_file_with_stats(instance, path)?.0)
+}
+
+/// Same as `scene_from_file`, also returns numbers about the loading
+pub fn scene_from_file_with_stats(
+    instance: &mut Instance,
+    path: &str,
+) -> Result<(Vec<u64>, LoadStats), MeshLoadError> {
+    let mut stats = LoadStats::default();
+
+    let start = Instant::now();
+    let scene = MeshRenderable::import_scene(path)?;
+    stats.import_time = start.elapsed();
+
+    let start = Instant::now();
+    let mut importer = SceneImporter {
+        scene: &scene,
+        directory: Path::new(path).parent().unwrap_or(Path::new("")),
+        materials: HashMap::new(),
+        objects: Vec::new(),
+        stats,
+    };
+
+    if let Some(ref root) = scene.root {
+        importer.import_node(root, None, M4x4::identity())?;
+    }
+
+    let ids: Vec<u64> = importer
+        .objects
+        .into_iter()
+        .map(|object| instance.register_object(object))
+        .collect();
+
+    for &id in ids.iter() {
+        let node = instance.get_object_mut(id).component_mut::<SceneNode>();
+        node.parent = node.parent.map(|parent| ids[parent as usize]);
+        for child in node.children.iter_mut() {
+            *child = ids[*child as usize];
+        }
+    }
+
+    let mut stats = importer.stats;
+    stats.conversion_time = start.elapsed();
+
+    Ok((ids, stats))
+}
+
+struct SceneImporter<'a> {
+    scene: &'a Scene,
+    directory: &'a Path,
+    /// Materials already converted, so that meshes sharing one also share its textures
+    materials: HashMap<u32, Material>,
+    /// Imported objects, only registered once the whole scene is imported. Until then the parent and
+    /// children of their `SceneNode` are indices in this list instead of ids.
+    objects: Vec<Object>,
+    stats: LoadStats,
+}
+
+impl<'a> SceneImporter<'a> {
+    /// Returns the index of the object of the node in `objects`
+    fn import_node(
+        &mut self,
+        node: &Node,
+        parent: Option<u64>,
+        parent_transform: M4x4,
+    ) -> Result<u64, MeshLoadError> {
+        let world_transform = parent_transform * to_m4x4(&node.transformation);
+        let (position, rotation, scale) = world_transform.decompose();
+        let transform = || Transform {
+            position,
+            rotation: rotation.to_euler(),
+        };
+
+        let mut object = Object::new();
+        object.add_component(Box::new(transform()));
+        object.add_component(Box::new(SceneNode::new(node.name.clone(), parent)));
+        if let [mesh] = node.meshes[..] {
+            object.add_component(Box::new(self.mesh(mesh, scale)?));
+        }
+
+        let index = self.objects.len() as u64;
+        self.objects.push(object);
+
+        let mut children = Vec::new();
+
+        if node.meshes.len() > 1 {
+            for &mesh in node.meshes.iter() {
+                let mut object = Object::new();
+                object.add_component(Box::new(transform()));
+                object.add_component(Box::new(SceneNode::new(
+                    self.scene.meshes[mesh as usize].name.clone(),
+                    Some(index),
+                )));
+                object.add_component(Box::new(self.mesh(mesh, scale)?));
+
+                children.push(self.objects.len() as u64);
+                self.objects.push(object);
+            }
+        }
+
+        for child in node.children.borrow().iter() {
+            children.push(self.import_node(child, Some(index), world_transform)?);
+        }
+
+        self.objects[index as usize]
+            .component_mut::<SceneNode>()
+            .children = children;
+
+        Ok(index)
+    }
+
+    fn mesh(&mut self, index: u32, scale: V3) -> Result<MeshRenderable, MeshLoadError> {
+        let mesh = &self.scene.meshes[index as usize];
+
+        let material = match self.materials.get(&mesh.material_index) {
+            Some(material) => Some(material.clone()),
+            None => match self.scene.materials.get(mesh.material_index as usize) {
+                Some(material) => {
+                    let material = Material::from_russimp(material, self.directory)?;
+                    self.materials.insert(mesh.material_index, material.clone());
+                    Some(material)
+                }
+                None => None,
+            },
+        };
+
+        let mut renderable = MeshRenderable::from_russimp(mesh, material, &mut self.stats);
+
+        if scale != V3::new(1.0, 1.0, 1.0) {
+            renderable.scale(scale);
+        }
+
+        Ok(renderable)
+    }
+}
+
+fn to_m4x4(matrix: &Matrix4x4) -> M4x4 {
+    let m = matrix;
+    M4x4::new([
+        [m.a1, m.a2, m.a3, m.a4],
+        [m.b1, m.b2, m.b3, m.b4],
+        [m.c1, m.c2, m.c3, m.c4],
+        [m.d1, m.d2, m.d3, m.d4],
+    ])
+}
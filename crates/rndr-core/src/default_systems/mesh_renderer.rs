@@ -1,14 +1,14 @@
-use std::sync::Arc;
+use std::ops::Range;
 
-use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
 
-use rndr_math::prelude::{M3x3, Vertex};
+use rndr_math::prelude::{M4x4, Vertex, V2, V3};
 
 use crate::object::Object;
-use crate::render::FragData;
+use crate::render::{DepthFunc, FragData, PixelRect, Uniforms, Varyings, VertexData};
 use crate::{
     default_components::{
-        render::{Camera, MeshRenderable},
+        render::{Camera, CullMode, MeshRenderable},
         Transform,
     },
     prelude::PixelGrid,
@@ -16,178 +16,565 @@ use crate::{
 
 pub struct MeshRendererSystem;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeshRenderPass {
+    /// Only the depth buffer is written, shaders aren't run
+    Depth,
+    /// Shaders are run and both color and depth are written
+    Color,
+    /// Same as `Color`, after a `Depth` pass already filled the depth buffer. Fragments with
+    /// `DepthFunc::Less` also pass against the depth they wrote themselves.
+    ColorAfterDepth,
+}
+
+/// Rows of pixels in each of the tiles rasterized in parallel
+const TILE_HEIGHT: u32 = 16;
+
+/// Vertices are snapped to 1/SUBPIXELS of a pixel, so that edge functions can be evaluated exactly
+const SUBPIXELS: i64 = 256;
+
+/// Triangles with vertices further than this amount of pixels from the screen are skipped,
+/// otherwise the fixed point edge functions could overflow
+const GUARD_BAND: f32 = (1 << 22) as f32;
+
+/// Triangle ready to be rasterized, with its vertices in fixed point raster space
+struct ScreenTriangle {
+    points: [(i64, i64); 3],
+    /// Normalized depth of each vertex, see `Camera::project`
+    depths: [f32; 3],
+    /// Depth of each vertex in camera space
+    zs: [f32; 3],
+    /// Weights of each vertex relative to the vertices of the original, unclipped triangle
+    weights: [[f32; 3]; 3],
+    /// Index of the mesh triangle this one comes from
+    triangle: usize,
+    /// Twice the area of the triangle, in subpixels. Always positive
+    area: i64,
+    x_range: Range<u32>,
+    y_range: Range<u32>,
+}
+
 impl MeshRendererSystem {
+    /// Draws the object as seen by the camera into the `viewport` part of the grid
+    #[allow(clippy::too_many_arguments)]
     pub fn render_mesh_object(
         &mut self,
         pixel_grid: &mut PixelGrid,
-        width: u32,
-        height: u32,
+        viewport: PixelRect,
         object: &Object,
         camera: &Camera,
         camera_transform: &Transform,
+        uniforms: &Uniforms,
+        pass: MeshRenderPass,
     ) {
-        let projection_matrix = camera.get_projection_matrix(camera_transform);
+        let object_mesh = object.component::<MeshRenderable>();
+
+        // Objects that don't test against the depth of the others are left out of the depth buffer,
+        // as well as the ones that don't hide what is behind them
+        if pass == MeshRenderPass::Depth
+            && (object_mesh.is_transparent() || !object_mesh.depth_write)
+        {
+            return;
+        }
+        let depth_func = match (pass, object_mesh.depth_func) {
+            (MeshRenderPass::Depth, DepthFunc::Always | DepthFunc::Never) => return,
+            (MeshRenderPass::ColorAfterDepth, DepthFunc::Less) => DepthFunc::LessEqual,
+            (_, depth_func) => depth_func,
+        };
 
-        let camera = Arc::from(camera);
+        let view_matrix = camera.view_matrix(camera_transform);
+        let projection_matrix =
+            camera.projection_matrix(camera.aspect_ratio(viewport.width, viewport.height));
 
         let object_transform = object.component::<Transform>();
-        let object_mesh = object.component::<MeshRenderable>();
 
-        let pixel_changes: Vec<_> = object_mesh
+        // Vertices as output by the vertex shader, or the ones of the mesh if it has none
+        let shaded: Option<(Vec<Vertex>, Vec<Varyings>)> =
+            object_mesh.vertex_shader.as_ref().map(|vertex_shader| {
+                object_mesh
+                    .vertices
+                    .par_iter()
+                    .enumerate()
+                    .map(|(index, vertex)| {
+                        let mut data = VertexData {
+                            vertex: *vertex,
+                            index,
+                            varyings: Varyings::default(),
+                        };
+                        vertex_shader.vertex(&mut data, uniforms);
+                        (data.vertex, data.varyings)
+                    })
+                    .unzip()
+            });
+
+        let (vertices, varyings) = match shaded {
+            Some((ref vertices, ref varyings)) => (vertices.as_slice(), Some(varyings.as_slice())),
+            None => (object_mesh.vertices.as_slice(), None),
+        };
+
+        let screen_triangles: Vec<ScreenTriangle> = object_mesh
             .triangles
             .par_iter()
-            .map(|triangle| {
-                self.render_triangle(
-                    width,
-                    height,
-                    &object_mesh,
-                    &object_transform,
-                    &camera,
-                    &camera_transform,
+            .enumerate()
+            .flat_map_iter(|(i, triangle)| {
+                self.setup_triangle(
+                    viewport,
+                    object_mesh,
+                    vertices,
+                    object_transform,
+                    camera,
+                    &view_matrix,
                     &projection_matrix,
+                    i,
                     triangle,
                 )
             })
             .collect();
 
-        for pixel_change in pixel_changes {
-            for pixel in pixel_change {
-                pixel_grid.set_pixel(pixel.0, pixel.1, pixel.2);
+        // Fragments are only shaded by the color passes
+        let prepared_shader = (pass != MeshRenderPass::Depth)
+            .then(|| object_mesh.shader.prepare(uniforms))
+            .flatten();
+        let shader = prepared_shader
+            .as_deref()
+            .unwrap_or(object_mesh.shader.as_ref());
+
+        let perspective = camera.perspective;
+        // Orthographic cameras look along the same direction from everywhere
+        let orthographic_view_direction = (!perspective).then(|| -camera_transform.fwd());
+
+        // Every tile goes through the triangles in the same order, so the result is the same as
+        // drawing them one after the other
+        pixel_grid.par_tiles_mut(TILE_HEIGHT).for_each(|mut tile| {
+            let rows = tile.rows.clone();
+
+            for screen_triangle in screen_triangles.iter() {
+                // V means vertex
+                let triangle = object_mesh.triangles[screen_triangle.triangle];
+                let first_v = vertices[triangle[0]];
+                let second_v = vertices[triangle[1]];
+                let third_v = vertices[triangle[2]];
+
+                self.raster_triangle(
+                    screen_triangle,
+                    rows.clone(),
+                    |quad_x, quad_y, quad, covered| {
+                        let zs = screen_triangle.zs;
+                        let depths = screen_triangle.depths;
+                        let original_weights = screen_triangle.weights;
+
+                        // Weights of each pixel of the quad relative to the vertices of the original, unclipped triangle.
+                        // Normalized depth is linear on screen, unlike the depth in camera space.
+                        let quad = quad.map(|(f, s, t)| {
+                            let depth = depths[0] * f + depths[1] * s + depths[2] * t;
+                            let (f, s, t) = if perspective {
+                                perspective_correct((f, s, t), (zs[0], zs[1], zs[2]))
+                            } else {
+                                (f, s, t)
+                            };
+                            let weights: [f32; 3] = std::array::from_fn(|i| {
+                                original_weights[0][i] * f
+                                    + original_weights[1][i] * s
+                                    + original_weights[2][i] * t
+                            });
+                            (depth, zs[0] * f + zs[1] * s + zs[2] * t, weights)
+                        });
+
+                        // Derivatives are shared by the whole quad, pixels outside of the triangle still
+                        // take part, with their weights extrapolated
+                        let (uv_dx, uv_dy) = if pass != MeshRenderPass::Depth {
+                            let uvs = quad.map(|(_, _, weights)| {
+                                V2::interpolate3(
+                                    (first_v.uv, weights[0]),
+                                    (second_v.uv, weights[1]),
+                                    (third_v.uv, weights[2]),
+                                )
+                            });
+                            (uvs[1] - uvs[0], uvs[2] - uvs[0])
+                        } else {
+                            (V2::default(), V2::default())
+                        };
+
+                        for (i, (z, view_depth, weights)) in quad.into_iter().enumerate() {
+                            if !covered[i] {
+                                continue;
+                            }
+                            let (x, y) = (quad_x + i as u32 % 2, quad_y + i as u32 / 2);
+
+                            // The fragment would be discarded by the depth test anyways, so it isn't worth shading.
+                            // After a depth pass this skips every fragment that doesn't end up on screen.
+                            if !depth_func.passes(z, tile.get_depth(x, y)) {
+                                continue;
+                            }
+
+                            if pass == MeshRenderPass::Depth {
+                                tile.write_depth(x, y, z);
+                                continue;
+                            }
+
+                            let interpolated_vertex = Vertex::interpolate(
+                                (first_v, weights[0]),
+                                (second_v, weights[1]),
+                                (third_v, weights[2]),
+                            );
+
+                            let [r, g, b] = interpolated_vertex.color;
+                            let interpolated_virtual_color = (z, [r, g, b, 255]);
+
+                            let space_position = interpolated_vertex
+                                .position
+                                .rotate(object_transform.rotation)
+                                + object_transform.position;
+
+                            let mut data = FragData {
+                                relative_position: interpolated_vertex.position,
+                                space_position,
+                                normal: interpolated_vertex
+                                    .normal
+                                    .rotate(object_transform.rotation)
+                                    .norm(),
+                                view_direction: orthographic_view_direction.unwrap_or_else(|| {
+                                    (camera_transform.position - space_position).norm()
+                                }),
+                                uv: interpolated_vertex.uv,
+                                uv_dx,
+                                uv_dy,
+                                view_depth,
+                                varyings: varyings
+                                    .map(|varyings| {
+                                        interpolate_varyings(varyings, triangle, weights)
+                                    })
+                                    .unwrap_or_default(),
+                                output_pixel: interpolated_virtual_color,
+                            };
+
+                            shader.frag(&mut data, uniforms);
+
+                            let (shaded_z, color) = data.output_pixel;
+                            // Shaders that move the fragment have to pass the depth test where it ended up
+                            if shaded_z != z && !depth_func.passes(shaded_z, tile.get_depth(x, y)) {
+                                continue;
+                            }
+                            // Fully transparent fragments are discarded, so that they don't hide anything either
+                            if object_mesh.is_transparent() && color[3] == 0 {
+                                continue;
+                            }
+
+                            let blend_mode = object_mesh.blend_mode;
+                            tile.write_color(x, y, blend_mode.blend(color, tile.get_color(x, y)));
+                            if object_mesh.depth_write {
+                                tile.write_depth(x, y, shaded_z);
+                            }
+                        }
+                    },
+                );
             }
-        }
+        });
     }
 
-    fn render_triangle(
+    /// Transforms, clips and projects a triangle of the mesh. Clipping can leave none, one or two triangles.
+    #[allow(clippy::too_many_arguments)]
+    fn setup_triangle(
         &self,
-        width: u32,
-        height: u32,
+        viewport: PixelRect,
         object_mesh: &MeshRenderable,
+        vertices: &[Vertex],
         object_transform: &Transform,
         camera: &Camera,
-        camera_transform: &Transform,
-        projection_matrix: &M3x3,
+        view_matrix: &M4x4,
+        projection_matrix: &M4x4,
+        index: usize,
         triangle: &[usize; 3],
-    ) -> Vec<(u32, u32, (f32, [u8; 3]))> {
-        let first_projected = camera.project_point(
-            *projection_matrix,
-            object_mesh.vertices[triangle[0]],
-            &object_transform,
-            &camera_transform,
-        );
+    ) -> Vec<ScreenTriangle> {
+        let camera_space =
+            triangle.map(|i| camera.to_camera_space(view_matrix, vertices[i], object_transform));
+
+        let clipped = clip_against_depth_range(camera_space, camera.near_plane, camera.far_plane);
+        // The depth in camera space is kept along, needed by perspective correct interpolation
+        let projected: Vec<_> = clipped
+            .iter()
+            .map(|(position, weights)| {
+                (
+                    camera.project(projection_matrix, *position),
+                    position.z,
+                    *weights,
+                )
+            })
+            .collect();
 
-        let second_projected = camera.project_point(
-            *projection_matrix,
-            object_mesh.vertices[triangle[1]],
-            &object_transform,
-            &camera_transform,
-        );
+        let mut ret = Vec::new();
 
-        let third_projected = camera.project_point(
-            *projection_matrix,
-            object_mesh.vertices[triangle[2]],
-            &object_transform,
-            &camera_transform,
+        // The clipped polygon is convex, so it can be split into a fan of triangles
+        for i in 1..projected.len().saturating_sub(1) {
+            let vertices = [projected[0], projected[i], projected[i + 1]];
+
+            if let Some(screen_triangle) =
+                ScreenTriangle::new(viewport, vertices, index, object_mesh.cull_mode)
+            {
+                ret.push(screen_triangle);
+            }
+        }
+
+        ret
+    }
+
+    /// Goes through the pixels in `rows` in 2x2 quads, calling `quad` with the position of the top left pixel
+    /// of every quad that has a pixel whose center is covered by the triangle. It gets the barycentric
+    /// coordinates of the 4 pixels, left to right and top to bottom, and which of them are covered.
+    /// Pixels outside of the triangle get extrapolated coordinates, so that derivatives can be computed across the quad.
+    ///
+    /// Pixels whose center lies exactly on an edge are only covered if it's a top or a left edge,
+    /// so that pixels on edges shared by two triangles are drawn exactly once.
+    fn raster_triangle<F>(&self, triangle: &ScreenTriangle, rows: Range<u32>, mut quad: F)
+    where
+        F: FnMut(u32, u32, [(f32, f32, f32); 4], [bool; 4]),
+    {
+        let x_range = triangle.x_range.clone();
+        let y_range = triangle.y_range.start.max(rows.start)..triangle.y_range.end.min(rows.end);
+
+        if x_range.is_empty() || y_range.is_empty() {
+            return;
+        }
+
+        let [first, second, third] = triangle.points;
+
+        // Each edge function gives the weight of the vertex opposite to it
+        let edges = [
+            Edge::new(second, third),
+            Edge::new(third, first),
+            Edge::new(first, second),
+        ];
+
+        // Quads are aligned to even pixels, tiles have an even height so they never split a quad
+        let (start_x, start_y) = (x_range.start & !1, y_range.start & !1);
+
+        let center = (
+            start_x as i64 * SUBPIXELS + SUBPIXELS / 2,
+            start_y as i64 * SUBPIXELS + SUBPIXELS / 2,
         );
+        let mut row_values = edges.each_ref().map(|edge| edge.at(center));
+
+        let area = triangle.area as f32;
+
+        for y in (start_y..y_range.end).step_by(2) {
+            let mut values = row_values;
+
+            for x in (start_x..x_range.end).step_by(2) {
+                let pixels: [[i64; 3]; 4] = std::array::from_fn(|i| {
+                    let (dx, dy) = (i as i64 % 2, i as i64 / 2);
+                    std::array::from_fn(|e| values[e] + edges[e].step_x * dx + edges[e].step_y * dy)
+                });
+
+                let covered: [bool; 4] = std::array::from_fn(|i| {
+                    let (px, py) = (x + i as u32 % 2, y + i as u32 / 2);
+                    x_range.contains(&px)
+                        && y_range.contains(&py)
+                        && pixels[i]
+                            .iter()
+                            .zip(&edges)
+                            .all(|(v, edge)| v + edge.bias >= 0)
+                });
+
+                if covered.contains(&true) {
+                    quad(
+                        x,
+                        y,
+                        pixels
+                            .map(|v| (v[0] as f32 / area, v[1] as f32 / area, v[2] as f32 / area)),
+                        covered,
+                    );
+                }
 
-        let near_plane = camera.near_plane;
+                for (value, edge) in values.iter_mut().zip(&edges) {
+                    *value += edge.step_x * 2;
+                }
+            }
 
-        if first_projected.z <= near_plane
-            || second_projected.z <= near_plane
-            || third_projected.z <= near_plane
-        {
-            return Vec::new();
+            for (value, edge) in row_values.iter_mut().zip(&edges) {
+                *value += edge.step_y * 2;
+            }
         }
+    }
+}
+
+impl ScreenTriangle {
+    /// `vertices` are points in normalized device coordinates with their depth in camera space and their weights
+    /// relative to the original triangle, they are stretched over `viewport`. None if the triangle is degenerate, too far off screen or culled.
+    fn new(
+        viewport: PixelRect,
+        mut vertices: [(V3, f32, [f32; 3]); 3],
+        triangle: usize,
+        cull_mode: CullMode,
+    ) -> Option<ScreenTriangle> {
+        let to_raster = |p: V3| {
+            // Rows go from the top down, while y goes up
+            let x = viewport.x as f32 + (p.x + 1.0) / 2.0 * viewport.width as f32;
+            let y = viewport.y as f32 + (1.0 - p.y) / 2.0 * viewport.height as f32;
+
+            if x.abs() > GUARD_BAND || y.abs() > GUARD_BAND {
+                return None;
+            }
 
-        // Values for the raster_triangle function to take. As it takes touples and
-        // the projected values would still be used in the future
-        let first = (first_projected.x, first_projected.y);
-        let second = (second_projected.x, second_projected.y);
-        let third = (third_projected.x, third_projected.y);
+            Some((
+                (x * SUBPIXELS as f32).round() as i64,
+                (y * SUBPIXELS as f32).round() as i64,
+            ))
+        };
 
-        self.raster_triangle(width, height, first, second, third, |f, s, t| {
-            // V means vertex
-            let first_v = object_mesh.vertices[triangle[0]];
-            let second_v = object_mesh.vertices[triangle[1]];
-            let third_v = object_mesh.vertices[triangle[2]];
+        let mut points = [
+            to_raster(vertices[0].0)?,
+            to_raster(vertices[1].0)?,
+            to_raster(vertices[2].0)?,
+        ];
 
-            let interpolated_vertex =
-                Vertex::interpolate((first_v, f), (second_v, s), (third_v, t));
+        let mut area = Edge::new(points[0], points[1]).at(points[2]);
 
-            let interpolated_virtual_color = (
-                first_projected.z * f + second_projected.z * s + third_projected.z * t,
-                interpolated_vertex.color,
-            );
+        if area == 0 {
+            return None;
+        }
 
-            let mut data = FragData {
-                relative_position: interpolated_vertex.position,
-                space_position: interpolated_vertex.position + object_transform.position,
-                output_pixel: interpolated_virtual_color,
-            };
+        // Counter clockwise triangles face the camera. Rows going down flip them clockwise on screen,
+        // which leaves them with a negative area
+        let front_facing = area < 0;
+        match cull_mode {
+            CullMode::Back if !front_facing => return None,
+            CullMode::Front if front_facing => return None,
+            _ => (),
+        }
 
-            object_mesh.shader.frag(&mut data);
+        // Edge functions are positive inside of triangles with this winding
+        if area < 0 {
+            points.swap(1, 2);
+            vertices.swap(1, 2);
+            area = -area;
+        }
 
-            data.output_pixel
+        let min_x = points.iter().map(|p| p.0).min().unwrap();
+        let max_x = points.iter().map(|p| p.0).max().unwrap();
+        let min_y = points.iter().map(|p| p.1).min().unwrap();
+        let max_y = points.iter().map(|p| p.1).max().unwrap();
+
+        let to_pixels = |v: i64, range: Range<u32>| {
+            (v / SUBPIXELS).clamp(range.start as i64, range.end as i64) as u32
+        };
+
+        Some(ScreenTriangle {
+            points,
+            depths: vertices.map(|v| v.0.z),
+            zs: vertices.map(|v| v.1),
+            weights: vertices.map(|v| v.2),
+            triangle,
+            area,
+            x_range: to_pixels(min_x, viewport.columns())
+                ..to_pixels(max_x + SUBPIXELS, viewport.columns()),
+            y_range: to_pixels(min_y, viewport.rows())
+                ..to_pixels(max_y + SUBPIXELS, viewport.rows()),
         })
     }
+}
 
-    fn raster_triangle<F>(
-        &self,
-        width: u32,
-        height: u32,
-        first: (f32, f32),
-        second: (f32, f32),
-        third: (f32, f32),
-        pixel: F,
-    ) -> Vec<(u32, u32, (f32, [u8; 3]))>
-    where
-        F: Fn(f32, f32, f32) -> (f32, [u8; 3]),
-    {
-        let total_area = triangle_area(first, second, third);
-
-        let width = (width / 2) as f32;
-        let height = (height / 2) as f32;
+/// Edge function of the edge going from `a` to `b`, evaluated incrementally across pixels
+struct Edge {
+    a: (i64, i64),
+    b: (i64, i64),
+    step_x: i64,
+    step_y: i64,
+    /// -1 for edges that don't own the pixels lying exactly on them
+    bias: i64,
+}
 
-        let x_start = first.0.min(second.0).min(third.0).max(-width).round() as i32;
+impl Edge {
+    fn new(a: (i64, i64), b: (i64, i64)) -> Edge {
+        let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+        let top_left = (dy == 0 && dx > 0) || dy < 0;
+
+        Edge {
+            a,
+            b,
+            step_x: -dy * SUBPIXELS,
+            step_y: dx * SUBPIXELS,
+            bias: if top_left { 0 } else { -1 },
+        }
+    }
 
-        let x_end = first.0.max(second.0).max(third.0).min(width).round() as i32;
+    fn at(&self, p: (i64, i64)) -> i64 {
+        (self.b.0 - self.a.0) * (p.1 - self.a.1) - (self.b.1 - self.a.1) * (p.0 - self.a.0)
+    }
+}
 
-        let y_start = first.1.min(second.1).min(third.1).max(-height).round() as i32;
+fn interpolate_varyings(
+    varyings: &[Varyings],
+    triangle: [usize; 3],
+    weights: [f32; 3],
+) -> Varyings {
+    std::array::from_fn(|i| {
+        varyings[triangle[0]][i] * weights[0]
+            + varyings[triangle[1]][i] * weights[1]
+            + varyings[triangle[2]][i] * weights[2]
+    })
+}
 
-        let y_end = first.1.max(second.1).max(third.1).min(height).round() as i32;
+/// Screen space barycentric coordinates don't interpolate linearly across a surface seen in perspective.
+/// Weighting them by 1/z gives the coordinates of the point on the actual triangle.
+fn perspective_correct(barycentric: (f32, f32, f32), zs: (f32, f32, f32)) -> (f32, f32, f32) {
+    let f = barycentric.0 / zs.0;
+    let s = barycentric.1 / zs.1;
+    let t = barycentric.2 / zs.2;
 
-        let mut ret = Vec::with_capacity(total_area as usize);
+    let sum = f + s + t;
+    (f / sum, s / sum, t / sum)
+}
 
-        for x in x_start..x_end {
-            for y in y_start..y_end {
-                let pt = (x as f32, y as f32);
+/// Clips a triangle in camera space against the near and far planes.
+///
+/// Returns the vertices of the remaining convex polygon (none, or 3 to 5 of them), each one with its
+/// weights relative to the vertices of the original triangle, so that their attributes can be interpolated.
+fn clip_against_depth_range(
+    vertices: [V3; 3],
+    near_plane: f32,
+    far_plane: f32,
+) -> Vec<(V3, [f32; 3])> {
+    const WEIGHTS: [[f32; 3]; 3] = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+    let polygon: Vec<(V3, [f32; 3])> = vertices.into_iter().zip(WEIGHTS).collect();
+    if vertices
+        .iter()
+        .all(|v| v.z >= near_plane && v.z <= far_plane)
+    {
+        return polygon;
+    }
 
-                let first_second = triangle_area(pt, first, second);
-                let first_third = triangle_area(pt, first, third);
-                let third_second = triangle_area(pt, third, second);
+    // Positive on the visible side of each plane
+    let polygon = clip_polygon(polygon, |p| p.z - near_plane);
+    clip_polygon(polygon, |p| far_plane - p.z)
+}
 
-                const EPSILON: f32 = 0.01;
+/// Keeps the part of a convex polygon where `distance` isn't negative
+fn clip_polygon(polygon: Vec<(V3, [f32; 3])>, distance: impl Fn(V3) -> f32) -> Vec<(V3, [f32; 3])> {
+    let mut ret = Vec::with_capacity(polygon.len() + 1);
 
-                if (first_second + first_third + third_second - total_area).abs() <= EPSILON {
-                    let screen_x = x + width as i32;
-                    let screen_y = height as i32 + y;
+    for i in 0..polygon.len() {
+        let (current, current_weights) = polygon[i];
+        let (next, next_weights) = polygon[(i + 1) % polygon.len()];
 
-                    let first = third_second / total_area;
-                    let second = first_third / total_area;
-                    let third = first_second / total_area;
+        let (current_distance, next_distance) = (distance(current), distance(next));
+        let current_inside = current_distance >= 0.0;
+        let next_inside = next_distance >= 0.0;
 
-                    ret.push((
-                        screen_x as u32,
-                        screen_y as u32,
-                        pixel(first, second, third),
-                    ));
-                }
-            }
+        if current_inside {
+            ret.push((current, current_weights));
         }
 
-        ret
+        // The edge crosses the plane, so the intersection becomes a vertex of the polygon
+        if current_inside != next_inside {
+            let t = current_distance / (current_distance - next_distance);
+            let position = current + (next - current) * t;
+            let weights = std::array::from_fn(|j| {
+                current_weights[j] + (next_weights[j] - current_weights[j]) * t
+            });
+            ret.push((position, weights));
+        }
     }
-}
 
-fn triangle_area(p1: (f32, f32), p2: (f32, f32), p3: (f32, f32)) -> f32 {
-    0.5 * ((p1.0 * (p2.1 - p3.1) + p2.0 * (p3.1 - p1.1) + p3.0 * (p1.1 - p2.1)).abs())
+    ret
 }
@@ -1,122 +1,184 @@
+#[cfg(feature = "sdl")]
 use sdl2::{
-    render::UpdateTextureError,
-    render::{Texture, TextureValueError, WindowCanvas},
+    render::{TextureValueError, UpdateTextureError},
     video::WindowBuildError,
     IntegerOrSdlError,
 };
 
 use thiserror::Error;
 
-use crate::events::EventPump;
+#[cfg(feature = "sdl")]
+use crate::backend::SdlBackend;
+use crate::backend::{HeadlessBackend, PresentationBackend};
+#[cfg(feature = "sdl")]
+use crate::events::{Event, EventPump};
 use crate::object::ObjectManager;
 use crate::prelude::{Object, PixelGrid, RenderContext};
-use crate::render::RenderError;
+use crate::render::{ExportError, FrameRecorder, RenderError, RenderStats};
 
 #[derive(Error, Debug)]
 pub enum InitError {
+    #[cfg(feature = "sdl")]
     #[error("Could not initialize SDL: {0}")]
     SdlInit(String),
+    #[cfg(feature = "sdl")]
     #[error("Could not initialize SDL video subsystem: {0}")]
     SdlVideoInit(String),
+    #[cfg(feature = "sdl")]
     #[error("Could not initialize SDL window: {0}")]
     SdlWindowInit(#[from] WindowBuildError),
+    #[cfg(feature = "sdl")]
     #[error("Could not initialize SDL canvas: {0}")]
     SdlCanvasInit(IntegerOrSdlError),
+    #[cfg(feature = "sdl")]
     #[error("Could not initialize SDL buffer texture: {0}")]
     SdlBufferTextureInit(#[from] TextureValueError),
+    #[cfg(feature = "sdl")]
     #[error("Could not initialize SDL event pump: {0}")]
     SdlEventPumpInit(String),
 }
 
 #[derive(Error, Debug)]
 pub enum RenderApplyError {
+    #[cfg(feature = "sdl")]
     #[error("Could not update buffer texture: {0}")]
     SdlUpdateTexture(#[from] UpdateTextureError),
+    #[cfg(feature = "sdl")]
     #[error("Could not copy buffer texture to canvas: {0}")]
     SdlCanvasCopy(String),
+    #[error("Could not record frame: {0}")]
+    Record(#[from] ExportError),
 }
 
 pub struct Instance {
-    pub event_pump: EventPump,
     pub object_manager: ObjectManager,
 
     pub(crate) render_context: RenderContext,
 
-    pub(crate) sdl_instance: SdlInstance,
-}
+    pub(crate) backend: Box<dyn PresentationBackend>,
+
+    recorder: Option<FrameRecorder>,
 
-pub(crate) struct SdlInstance {
-    pub canvas: WindowCanvas,
-    pub buff_texture: Texture,
+    /// None when the instance doesn't own an SDL window, events are read through `poll_events`
+    #[cfg(feature = "sdl")]
+    event_pump: Option<EventPump>,
 }
 
 impl Instance {
+    /// Creates an instance that presents its frames in an SDL window
+    #[cfg(feature = "sdl")]
     pub fn init(
         width: u32,
         height: u32,
         buff_width: u32,
         buff_height: u32,
     ) -> Result<Instance, InitError> {
-        let sdl_ctx = sdl2::init().map_err(InitError::SdlInit)?;
-        let video = sdl_ctx.video().map_err(InitError::SdlVideoInit)?;
-        let window = video
-            .window("Test", width, height)
-            .position_centered()
-            .input_grabbed()
-            .build()?;
-
-        let canvas = window
-            .into_canvas()
-            .build()
-            .map_err(InitError::SdlCanvasInit)?;
-
-        let mouse = sdl_ctx.mouse();
-        mouse.set_relative_mouse_mode(true);
-
-        let event_pump = sdl_ctx.event_pump().map_err(InitError::SdlEventPumpInit)?;
-        let buff_texture = canvas.create_texture_streaming(
-            sdl2::pixels::PixelFormatEnum::RGB24,
+        let (backend, event_pump) = SdlBackend::new(width, height, buff_width, buff_height)?;
+
+        let mut instance = Instance::init_with_backend(buff_width, buff_height, Box::new(backend));
+        instance.event_pump = Some(event_pump);
+
+        Ok(instance)
+    }
+
+    /// Creates an instance that doesn't open any window. The last applied frame can be read back
+    /// with `presented_frame`.
+    pub fn init_headless(buff_width: u32, buff_height: u32) -> Instance {
+        Instance::init_with_backend(
             buff_width,
             buff_height,
-        )?;
+            Box::new(HeadlessBackend::new(buff_width, buff_height)),
+        )
+    }
 
-        Ok(Instance {
-            event_pump,
+    pub fn init_with_backend(
+        buff_width: u32,
+        buff_height: u32,
+        backend: Box<dyn PresentationBackend>,
+    ) -> Instance {
+        Instance {
             render_context: RenderContext::new(buff_width, buff_height),
-            sdl_instance: SdlInstance {
-                buff_texture,
-                canvas,
-            },
+            backend,
+            recorder: None,
             object_manager: ObjectManager::new(),
-        })
+            #[cfg(feature = "sdl")]
+            event_pump: None,
+        }
+    }
+
+    /// Events received by the window since the last poll. Always empty if there's no window.
+    #[cfg(feature = "sdl")]
+    pub fn poll_events(&mut self) -> Vec<Event> {
+        match self.event_pump {
+            Some(ref mut event_pump) => event_pump.poll_iter().collect(),
+            None => Vec::new(),
+        }
     }
 
     pub fn configure_mesh_rendering_system(&mut self) {
         self.render_context.configure_mesh_renderer();
     }
 
+    /// Fill the depth buffer before shading, so that only the fragments that end up visible get shaded.
+    /// Worth it in scenes with a lot of overdraw or expensive shaders.
+    pub fn set_depth_prepass(&mut self, enabled: bool) {
+        self.render_context.depth_prepass = enabled;
+    }
+
     pub fn render(&mut self) -> Result<(), RenderError> {
         self.render_context.render_objects(&self.object_manager)
     }
 
+    /// Renders only the depth of the objects, no shaders are run and no colors are written
+    pub fn render_depth(&mut self) -> Result<(), RenderError> {
+        self.render_context.render_depth(&self.object_manager)
+    }
+
+    /// Renders the camera of the object `id` into the whole frame, instead of the first registered camera.
+    /// Goes back to the first registered camera if the object is removed or has no `Camera`.
+    pub fn set_main_camera(&mut self, id: u64) {
+        self.render_context.main_camera = Some(id);
+    }
+
+    /// Object whose camera renders into the whole frame, see `set_main_camera`. Cameras with a viewport
+    /// are rendered on top of it.
+    pub fn main_camera(&self) -> Option<u64> {
+        self.render_context
+            .find_main_camera(&self.object_manager)
+            .map(|object| object.id())
+    }
+
+    /// Numbers about the last rendered frame, such as how many objects were frustum culled
+    pub fn render_stats(&self) -> RenderStats {
+        self.render_context.stats
+    }
+
     pub fn apply_render(&mut self) -> Result<(), RenderApplyError> {
-        self.sdl_instance.buff_texture.update(
-            None,
-            self.render_context.pixel_grid.get_pixel_data(),
-            (self.render_context.pixel_grid.width * 3) as usize,
-        )?;
-
-        self.sdl_instance
-            .canvas
-            .copy(&self.sdl_instance.buff_texture, None, None)
-            .map_err(RenderApplyError::SdlCanvasCopy)?;
-        self.sdl_instance.canvas.present();
+        self.backend.present(&self.render_context.pixel_grid)?;
+
+        if let Some(ref mut recorder) = self.recorder {
+            recorder.record(&self.render_context.pixel_grid)?;
+        }
 
         self.render_context.pixel_grid.clear();
 
         Ok(())
     }
 
+    /// Every frame applied from now on will also be written by `recorder`
+    pub fn start_recording(&mut self, recorder: FrameRecorder) {
+        self.recorder = Some(recorder);
+    }
+
+    pub fn stop_recording(&mut self) -> Option<FrameRecorder> {
+        self.recorder.take()
+    }
+
+    /// The last frame passed to the backend, if the backend keeps it
+    pub fn presented_frame(&self) -> Option<&PixelGrid> {
+        self.backend.presented_frame()
+    }
+
     pub fn register_object(&mut self, object: Object) -> u64 {
         self.object_manager.register_object(object)
     }
@@ -1,3 +1,4 @@
+pub mod backend;
 pub mod instance;
 pub mod object;
 pub mod render;
@@ -12,6 +13,7 @@ pub mod prelude {
     pub use super::render::*;
 }
 
+#[cfg(feature = "sdl")]
 pub mod events {
     pub use sdl2::event::*;
     pub use sdl2::keyboard::*;
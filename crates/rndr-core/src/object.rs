@@ -80,6 +80,12 @@ pub struct ObjectManager {
     objects: HashMap<u64, Object>,
 }
 
+impl Default for ObjectManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ObjectManager {
     pub fn new() -> ObjectManager {
         ObjectManager {
@@ -0,0 +1,134 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use image::codecs::pnm::{PnmEncoder, PnmSubtype, SampleEncoding};
+use image::{ExtendedColorType, ImageEncoder};
+use thiserror::Error;
+
+use super::PixelGrid;
+
+#[derive(Error, Debug)]
+pub enum ExportError {
+    #[error("Could not infer image format from path: {0}")]
+    UnknownFormat(PathBuf),
+    #[error("Could not write file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not write image: {0}")]
+    Image(#[from] image::ImageError),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    /// Binary portable pixmap (P6)
+    Ppm,
+    Png,
+    Tga,
+}
+
+impl ImageFormat {
+    pub fn from_path(path: &Path) -> Option<ImageFormat> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "ppm" => Some(ImageFormat::Ppm),
+            "png" => Some(ImageFormat::Png),
+            "tga" => Some(ImageFormat::Tga),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Ppm => "ppm",
+            ImageFormat::Png => "png",
+            ImageFormat::Tga => "tga",
+        }
+    }
+}
+
+impl PixelGrid {
+    /// Writes the current colors to `path`, the format is inferred from the extension
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ExportError> {
+        let path = path.as_ref();
+        let format = ImageFormat::from_path(path)
+            .ok_or_else(|| ExportError::UnknownFormat(path.to_path_buf()))?;
+        self.save_with_format(path, format)
+    }
+
+    /// Writes the normalized depth buffer as a grayscale image, see `depth_visualization`
+    pub fn save_depth(&self, path: impl AsRef<Path>) -> Result<(), ExportError> {
+        self.depth_visualization().save(path)
+    }
+
+    pub fn save_with_format(
+        &self,
+        path: impl AsRef<Path>,
+        format: ImageFormat,
+    ) -> Result<(), ExportError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        let (data, width, height) = (self.get_pixel_data(), self.width, self.height);
+
+        match format {
+            // The generic pnm encoder would pick PAM (P7), which most viewers can't open
+            ImageFormat::Ppm => PnmEncoder::new(&mut writer)
+                .with_subtype(PnmSubtype::Pixmap(SampleEncoding::Binary))
+                .write_image(data, width, height, ExtendedColorType::Rgb8)?,
+            ImageFormat::Png => image::write_buffer_with_format(
+                &mut writer,
+                data,
+                width,
+                height,
+                ExtendedColorType::Rgb8,
+                image::ImageFormat::Png,
+            )?,
+            ImageFormat::Tga => image::write_buffer_with_format(
+                &mut writer,
+                data,
+                width,
+                height,
+                ExtendedColorType::Rgb8,
+                image::ImageFormat::Tga,
+            )?,
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes every frame it receives into a directory as `frame_0001.png`, `frame_0002.png`...
+#[derive(Debug)]
+pub struct FrameRecorder {
+    directory: PathBuf,
+    format: ImageFormat,
+    next_frame: u32,
+}
+
+impl FrameRecorder {
+    pub fn new(directory: impl Into<PathBuf>, format: ImageFormat) -> FrameRecorder {
+        FrameRecorder {
+            directory: directory.into(),
+            format,
+            next_frame: 1,
+        }
+    }
+
+    /// Amount of frames written so far
+    pub fn frames_recorded(&self) -> u32 {
+        self.next_frame - 1
+    }
+
+    /// Writes `pixel_grid` as the next frame of the sequence and returns the path of the written file
+    pub fn record(&mut self, pixel_grid: &PixelGrid) -> Result<PathBuf, ExportError> {
+        std::fs::create_dir_all(&self.directory)?;
+
+        let path = self.directory.join(format!(
+            "frame_{:04}.{}",
+            self.next_frame,
+            self.format.extension()
+        ));
+        pixel_grid.save_with_format(&path, self.format)?;
+
+        self.next_frame += 1;
+        Ok(path)
+    }
+}
@@ -0,0 +1,122 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use russimp::material::{DataContent, PropertyTypeInfo, TextureType};
+
+use super::{Texture, TextureError};
+
+/// Surface properties of a mesh, usually imported along with it. Used by `MaterialShader`.
+#[derive(Debug, Clone)]
+pub struct Material {
+    pub name: String,
+    /// Base color of the surface, multiplied by the vertex colors and the diffuse texture
+    pub diffuse: [u8; 3],
+    /// Color of the highlights
+    pub specular: [u8; 3],
+    /// Color the surface gives off without any light
+    pub emissive: [u8; 3],
+    /// How focused the highlights are
+    pub shininess: f32,
+    /// 1 is fully opaque. Output as the alpha of `MaterialShader`, imported meshes with a lower opacity are
+    /// blended with `BlendMode::Alpha`
+    pub opacity: f32,
+    pub diffuse_texture: Option<Arc<Texture>>,
+    /// Multiplies the specular color
+    pub specular_texture: Option<Arc<Texture>>,
+}
+
+impl Default for Material {
+    fn default() -> Material {
+        Material {
+            name: String::new(),
+            diffuse: [255; 3],
+            specular: [0; 3],
+            emissive: [0; 3],
+            shininess: 32.0,
+            opacity: 1.0,
+            diffuse_texture: None,
+            specular_texture: None,
+        }
+    }
+}
+
+impl Material {
+    /// Converts a material parsed by russimp. Textures that aren't embedded in the file
+    /// are loaded from paths relative to `directory`.
+    pub(crate) fn from_russimp(
+        material: &russimp::material::Material,
+        directory: &Path,
+    ) -> Result<Material, TextureError> {
+        let mut ret = Material::default();
+        // glTF stores its color as the base color, which should win over the diffuse color it also sets
+        let mut base_color = None;
+
+        for property in material.properties.iter() {
+            match (property.key.as_str(), &property.data) {
+                ("?mat.name", PropertyTypeInfo::String(name)) => ret.name = name.clone(),
+                ("$clr.diffuse", PropertyTypeInfo::FloatArray(c)) => ret.diffuse = to_color(c),
+                ("$clr.base", PropertyTypeInfo::FloatArray(c)) => base_color = Some(to_color(c)),
+                ("$clr.specular", PropertyTypeInfo::FloatArray(c)) => ret.specular = to_color(c),
+                ("$clr.emissive", PropertyTypeInfo::FloatArray(c)) => ret.emissive = to_color(c),
+                ("$mat.shininess", PropertyTypeInfo::FloatArray(v)) if !v.is_empty() => {
+                    ret.shininess = v[0]
+                }
+                ("$mat.opacity", PropertyTypeInfo::FloatArray(v)) if !v.is_empty() => {
+                    ret.opacity = v[0]
+                }
+                _ => (),
+            }
+        }
+
+        if let Some(base_color) = base_color {
+            ret.diffuse = base_color;
+        }
+
+        ret.diffuse_texture = match load_texture(material, TextureType::BaseColor, directory)? {
+            Some(texture) => Some(texture),
+            None => load_texture(material, TextureType::Diffuse, directory)?,
+        };
+        ret.specular_texture = load_texture(material, TextureType::Specular, directory)?;
+
+        Ok(ret)
+    }
+}
+
+/// Loads the texture of the given type, either embedded in the file or next to it
+fn load_texture(
+    material: &russimp::material::Material,
+    texture_type: TextureType,
+    directory: &Path,
+) -> Result<Option<Arc<Texture>>, TextureError> {
+    if let Some(embedded) = material.textures.get(&texture_type) {
+        let embedded = embedded.borrow();
+
+        let texture = match embedded.data {
+            DataContent::Texel(ref texels) => {
+                let data: Vec<u8> = texels.iter().flat_map(|t| [t.r, t.g, t.b, t.a]).collect();
+                Texture::from_rgba(embedded.width, embedded.height, &data)?
+            }
+            // Compressed textures are stored as the bytes of the image file
+            DataContent::Bytes(ref bytes) => Texture::from_memory(bytes)?,
+        };
+        return Ok(Some(Arc::new(texture)));
+    }
+
+    let file = material.properties.iter().find_map(|p| match p.data {
+        PropertyTypeInfo::String(ref file)
+            if p.key == "$tex.file" && p.semantic == texture_type =>
+        {
+            Some(file)
+        }
+        _ => None,
+    });
+
+    match file {
+        Some(file) => Ok(Some(Arc::new(Texture::from_file(directory.join(file))?))),
+        None => Ok(None),
+    }
+}
+
+fn to_color(c: &[f32]) -> [u8; 3] {
+    std::array::from_fn(|i| (c.get(i).copied().unwrap_or(1.0).clamp(0.0, 1.0) * 255.0) as u8)
+}
@@ -1,22 +1,55 @@
+pub mod export;
+pub mod material;
 pub mod pixel;
 pub mod shader;
+pub mod target;
+pub mod texture;
+pub mod uniforms;
 
-pub use pixel::PixelGrid;
+pub use export::{ExportError, FrameRecorder, ImageFormat};
+pub use material::Material;
+pub use pixel::{BlendMode, DepthFunc, PixelGrid, PixelRect};
 pub use shader::FragData;
 pub use shader::FragShader;
+pub use shader::{LitShader, MaterialShader, RenderTargetShader};
+pub use shader::{Varyings, VertexData, VertexShader};
+pub use target::RenderTarget;
+pub use texture::{Filter, MipLevel, Sampler, Texture, TextureError, WrapMode};
+pub use uniforms::{ShaderParams, UniformValue, Uniforms};
+
+use std::time::Instant;
 
 use thiserror::Error;
 
 use crate::default_components::{
-    render::{Camera, MeshRenderable},
+    render::{Camera, DirectionalLight, Light, MeshRenderable, PointLight, SpotLight},
     Transform,
 };
-use crate::default_systems::mesh_renderer::MeshRendererSystem;
-use crate::prelude::ObjectManager;
+use crate::default_systems::mesh_renderer::{MeshRenderPass, MeshRendererSystem};
+use crate::prelude::{Object, ObjectManager};
 
 pub(crate) struct RenderContext {
     pub pixel_grid: PixelGrid,
+    /// Whether to fill the depth buffer before running any shader, so that only visible fragments get shaded
+    pub depth_prepass: bool,
+    pub stats: RenderStats,
+    /// Object whose camera is rendered into the whole frame, see `main_camera`
+    pub main_camera: Option<u64>,
     mesh_renderer: Option<MeshRendererSystem>,
+    /// Shaders get the time since this instant
+    start: Instant,
+    frame: u64,
+}
+
+/// Numbers about the last rendered frame
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RenderStats {
+    /// Objects sent to the mesh renderer, once for each camera that rendered them
+    pub objects_rendered: u32,
+    /// Objects skipped because they were outside of the camera frustum, once for each camera
+    pub objects_culled: u32,
+    /// Cameras rendered, the main one, the ones with a viewport and the ones with a target
+    pub cameras_rendered: u32,
 }
 
 #[derive(Error, Debug)]
@@ -29,7 +62,12 @@ impl RenderContext {
     pub fn new(buff_width: u32, buff_height: u32) -> RenderContext {
         RenderContext {
             pixel_grid: PixelGrid::new(buff_width, buff_height),
+            depth_prepass: false,
+            stats: RenderStats::default(),
+            main_camera: None,
             mesh_renderer: None,
+            start: Instant::now(),
+            frame: 0,
         }
     }
 
@@ -40,38 +78,278 @@ impl RenderContext {
     }
 
     pub fn render_objects(&mut self, object_manager: &ObjectManager) -> Result<(), RenderError> {
-        if let Some(ref mut mesh_renderer) = self.mesh_renderer {
-            let width = self.pixel_grid.width;
-            let height = self.pixel_grid.height;
+        if self.depth_prepass {
+            self.render_cameras(
+                object_manager,
+                &[MeshRenderPass::Depth, MeshRenderPass::ColorAfterDepth],
+            )?;
+        } else {
+            self.render_cameras(object_manager, &[MeshRenderPass::Color])?;
+        }
+
+        self.frame += 1;
+        Ok(())
+    }
 
-            let camera_object = object_manager
+    /// Renders only into the depth buffer
+    pub fn render_depth(&mut self, object_manager: &ObjectManager) -> Result<(), RenderError> {
+        self.render_cameras(object_manager, &[MeshRenderPass::Depth])
+    }
+
+    /// The camera set with `main_camera` if it still exists, the first registered one otherwise.
+    /// Cameras rendering into a target are skipped.
+    pub fn find_main_camera<'a>(&self, object_manager: &'a ObjectManager) -> Option<&'a Object> {
+        let renders_to_frame = |object: &&Object| {
+            object
+                .try_component::<Camera>()
+                .is_some_and(|camera| camera.target.is_none())
+        };
+
+        let main_camera = self
+            .main_camera
+            .and_then(|id| object_manager.try_get_object(id))
+            .filter(renders_to_frame);
+
+        main_camera.or_else(|| {
+            object_manager
                 .objects_iter()
-                .find(|obj| obj.has_component::<Camera>());
+                .filter(renders_to_frame)
+                .min_by_key(|object| object.id())
+        })
+    }
 
-            if camera_object.is_none() {
-                return Err(RenderError::NoCamera);
-            }
+    /// Cameras rendering into a target, in the order they are rendered
+    fn target_cameras<'a>(&self, object_manager: &'a ObjectManager) -> Vec<&'a Object> {
+        let mut cameras: Vec<&Object> = object_manager
+            .objects_iter()
+            .filter(|object| {
+                object
+                    .try_component::<Camera>()
+                    .is_some_and(|camera| camera.target.is_some())
+            })
+            .collect();
+        cameras.sort_by_key(|object| (object.component::<Camera>().render_order, object.id()));
+        cameras
+    }
+
+    /// Cameras to render this frame in order, along with the pixels they render into
+    fn cameras_to_render<'a>(
+        &self,
+        object_manager: &'a ObjectManager,
+    ) -> Result<Vec<(&'a Object, PixelRect)>, RenderError> {
+        let main_camera = self
+            .find_main_camera(object_manager)
+            .ok_or(RenderError::NoCamera)?;
+
+        let mut with_viewport: Vec<&Object> = object_manager
+            .objects_iter()
+            .filter(|object| {
+                object
+                    .try_component::<Camera>()
+                    .is_some_and(|camera| camera.viewport.is_some() && camera.target.is_none())
+            })
+            .collect();
+        with_viewport
+            .sort_by_key(|object| (object.component::<Camera>().render_order, object.id()));
+
+        let mut cameras = Vec::with_capacity(with_viewport.len() + 1);
+        if main_camera.component::<Camera>().viewport.is_none() {
+            cameras.push((main_camera, self.pixel_grid.rect()));
+        }
+        for object in with_viewport {
+            let viewport = object.component::<Camera>().viewport.unwrap();
+            cameras.push((
+                object,
+                viewport.pixels(self.pixel_grid.width, self.pixel_grid.height),
+            ));
+        }
 
-            let camera = camera_object.unwrap().component::<Camera>();
+        Ok(cameras)
+    }
+
+    fn render_cameras(
+        &mut self,
+        object_manager: &ObjectManager,
+        passes: &[MeshRenderPass],
+    ) -> Result<(), RenderError> {
+        if self.mesh_renderer.is_none() {
+            return Ok(());
+        }
 
-            let camera_transform = camera_object.unwrap().component::<Transform>();
+        let cameras = self.cameras_to_render(object_manager)?;
+        let lights = collect_lights(object_manager);
+        let time = self.start.elapsed().as_secs_f32();
 
-            for object in object_manager.objects_iter() {
-                if !object.has_component::<MeshRenderable>() {
-                    continue;
-                }
+        self.stats = RenderStats::default();
 
-                mesh_renderer.render_mesh_object(
-                    &mut self.pixel_grid,
-                    width,
-                    height,
-                    &object,
-                    &camera,
-                    &camera_transform,
+        // Targets are only sampled for their colors, a depth only render leaves them as they are
+        let target_cameras = if passes.iter().any(|pass| *pass != MeshRenderPass::Depth) {
+            self.target_cameras(object_manager)
+        } else {
+            Vec::new()
+        };
+
+        for camera_object in target_cameras {
+            let camera = camera_object.component::<Camera>();
+            let target = camera.target.as_ref().unwrap();
+
+            target.render(|pixel_grid| {
+                let viewport = camera
+                    .viewport
+                    .unwrap_or_default()
+                    .pixels(target.width, target.height);
+                self.render_camera(
+                    Some(pixel_grid),
+                    viewport,
+                    object_manager,
+                    camera_object,
+                    &lights,
+                    time,
+                    passes,
                 );
+            });
+        }
+
+        for (i, &(camera_object, viewport)) in cameras.iter().enumerate() {
+            // The frame starts cleared, cameras after the first one draw over what is already there
+            if i > 0 {
+                self.pixel_grid.clear_rect(viewport);
             }
+
+            self.render_camera(
+                None,
+                viewport,
+                object_manager,
+                camera_object,
+                &lights,
+                time,
+                passes,
+            );
         }
 
         Ok(())
     }
+
+    /// Renders the objects seen by the camera into the `viewport` part of `pixel_grid`, or of the frame
+    /// if there's none
+    #[allow(clippy::too_many_arguments)]
+    fn render_camera(
+        &mut self,
+        pixel_grid: Option<&mut PixelGrid>,
+        viewport: PixelRect,
+        object_manager: &ObjectManager,
+        camera_object: &Object,
+        lights: &[Light],
+        time: f32,
+        passes: &[MeshRenderPass],
+    ) {
+        if viewport.is_empty() {
+            return;
+        }
+        let Some(ref mut mesh_renderer) = self.mesh_renderer else {
+            return;
+        };
+        let pixel_grid = pixel_grid.unwrap_or(&mut self.pixel_grid);
+
+        let camera = camera_object.component::<Camera>();
+        let camera_transform = camera_object.component::<Transform>();
+        let frustum = camera.frustum(camera_transform, viewport.width, viewport.height);
+
+        let (mut visible, culled): (Vec<&Object>, Vec<&Object>) = object_manager
+            .objects_iter()
+            .filter(|object| object.has_component::<MeshRenderable>())
+            .partition(|object| {
+                object
+                    .component::<MeshRenderable>()
+                    .culling_sphere(object.component::<Transform>())
+                    .is_none_or(|(center, radius)| frustum.intersects_sphere(center, radius))
+            });
+
+        // Objects are drawn in registration order, overlays last so that they end up on top
+        visible.sort_by_key(|object| {
+            let depth_func = object.component::<MeshRenderable>().depth_func;
+            (depth_func == DepthFunc::Always, object.id())
+        });
+
+        self.stats.objects_rendered += visible.len() as u32;
+        self.stats.objects_culled += culled.len() as u32;
+        self.stats.cameras_rendered += 1;
+
+        let (opaque, mut transparent): (Vec<&Object>, Vec<&Object>) = visible
+            .into_iter()
+            .partition(|object| !object.component::<MeshRenderable>().is_transparent());
+
+        // Transparent objects blend with what is already drawn, so they go after every opaque one, from the
+        // furthest to the closest
+        let distance = |object: &Object| {
+            let (center, _) = object
+                .component::<MeshRenderable>()
+                .bounding_sphere(object.component::<Transform>());
+            (center - camera_transform.position).mag()
+        };
+        let is_overlay =
+            |object: &Object| object.component::<MeshRenderable>().depth_func == DepthFunc::Always;
+        transparent.sort_by(|a, b| {
+            is_overlay(a)
+                .cmp(&is_overlay(b))
+                .then(distance(b).total_cmp(&distance(a)))
+        });
+
+        let mut draws: Vec<(&Object, MeshRenderPass)> = passes
+            .iter()
+            .flat_map(|&pass| opaque.iter().map(move |object| (*object, pass)))
+            .collect();
+        if passes.iter().any(|pass| *pass != MeshRenderPass::Depth) {
+            draws.extend(
+                transparent
+                    .iter()
+                    .map(|object| (*object, MeshRenderPass::Color)),
+            );
+        }
+
+        for (object, pass) in draws {
+            let uniforms = Uniforms {
+                time,
+                frame: self.frame,
+                camera_position: camera_transform.position,
+                camera_direction: camera_transform.fwd(),
+                lights,
+                object_id: object.id(),
+                params: &object.component::<MeshRenderable>().shader_params,
+                material: object.component::<MeshRenderable>().material.as_ref(),
+            };
+
+            mesh_renderer.render_mesh_object(
+                pixel_grid,
+                viewport,
+                object,
+                camera,
+                camera_transform,
+                &uniforms,
+                pass,
+            );
+        }
+    }
+}
+
+fn collect_lights(object_manager: &ObjectManager) -> Vec<Light> {
+    let mut lights = Vec::new();
+
+    for object in object_manager.objects_iter() {
+        let Some(transform) = object.try_component::<Transform>() else {
+            continue;
+        };
+
+        if let Some(light) = object.try_component::<DirectionalLight>() {
+            lights.push(Light::directional(light, transform));
+        }
+        if let Some(light) = object.try_component::<PointLight>() {
+            lights.push(Light::point(light, transform));
+        }
+        if let Some(light) = object.try_component::<SpotLight>() {
+            lights.push(Light::spot(light, transform));
+        }
+    }
+
+    lights
 }
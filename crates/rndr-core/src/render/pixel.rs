@@ -1,3 +1,7 @@
+use rayon::iter::{IndexedParallelIterator, ParallelIterator};
+use rayon::slice::ParallelSliceMut;
+
+#[derive(Clone)]
 pub struct PixelGrid {
     pub width: u32,
     pub height: u32,
@@ -5,8 +9,99 @@ pub struct PixelGrid {
     pixel_zs: Vec<f32>,
 }
 
-// Arbitrary large number, so that pretty much any ordinary pixel would beat it and not get z occluded
-const DEFAULT_Z: f32 = 100_000_000.0;
+/// Rectangle of pixels of a `PixelGrid`, from its top left corner
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixelRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl PixelRect {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> PixelRect {
+        PixelRect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn columns(&self) -> std::ops::Range<u32> {
+        self.x..self.x + self.width
+    }
+
+    pub fn rows(&self) -> std::ops::Range<u32> {
+        self.y..self.y + self.height
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+}
+
+/// Depth of the far plane. Depths are normalized between 0 at the near plane of the camera and 1 at its far
+/// plane, so pixels nothing was drawn on keep this value.
+pub const DEFAULT_Z: f32 = 1.0;
+
+/// Comparison of the depth of a fragment against the one already in the buffer, the fragment is only drawn if it passes
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DepthFunc {
+    /// Closer than what is already there
+    Less,
+    /// Closer than or as close as what is already there, so that the last of two overlapping surfaces ends up on top
+    #[default]
+    LessEqual,
+    /// Drawn over everything, for overlays. Objects using it are drawn after the others
+    Always,
+    /// Never drawn
+    Never,
+}
+
+impl DepthFunc {
+    pub fn passes(&self, depth: f32, current: f32) -> bool {
+        match self {
+            DepthFunc::Less => depth < current,
+            DepthFunc::LessEqual => depth <= current,
+            DepthFunc::Always => true,
+            DepthFunc::Never => false,
+        }
+    }
+}
+
+/// How the color of a fragment is combined with the color already in the buffer, using the alpha of the fragment
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BlendMode {
+    /// The fragment replaces what is there, its alpha is ignored
+    #[default]
+    Opaque,
+    /// The fragment is laid over what is there, for glass and fading objects
+    Alpha,
+    /// The fragment brightens what is there, for particles and glows
+    Additive,
+    /// The fragment tints what is there
+    Multiply,
+}
+
+impl BlendMode {
+    /// Color of a pixel of color `destination` once `source` is drawn over it
+    pub fn blend(&self, source: [u8; 4], destination: [u8; 3]) -> [u8; 3] {
+        let alpha = source[3] as f32 / 255.0;
+
+        std::array::from_fn(|i| {
+            let (src, dst) = (source[i] as f32, destination[i] as f32);
+            let color = match self {
+                BlendMode::Opaque => src,
+                BlendMode::Alpha => src * alpha + dst * (1.0 - alpha),
+                BlendMode::Additive => dst + src * alpha,
+                // Fully transparent fragments leave the color as it is
+                BlendMode::Multiply => dst * (1.0 - alpha + alpha * src / 255.0),
+            };
+            color.round().clamp(0.0, 255.0) as u8
+        })
+    }
+}
 
 impl PixelGrid {
     pub fn new(width: u32, height: u32) -> PixelGrid {
@@ -26,9 +121,10 @@ impl PixelGrid {
         )
     }
 
+    /// Writes the pixel if its depth passes `DepthFunc::LessEqual`
     pub fn set_pixel(&mut self, x: u32, y: u32, pixel: (f32, [u8; 3])) {
         let current_pixel = self.get_pixel(x, y);
-        if pixel.0 > *current_pixel.0 {
+        if !DepthFunc::LessEqual.passes(pixel.0, *current_pixel.0) {
             return;
         }
 
@@ -39,6 +135,43 @@ impl PixelGrid {
         current_pixel.1[2] = pixel.1[2];
     }
 
+    /// Writes only the depth of the pixel, if it passes `DepthFunc::Less`
+    pub fn set_depth(&mut self, x: u32, y: u32, z: f32) {
+        let current_z = self.get_pixel(x, y).0;
+        if DepthFunc::Less.passes(z, *current_z) {
+            *current_z = z;
+        }
+    }
+
+    pub fn get_depth(&self, x: u32, y: u32) -> f32 {
+        self.pixel_zs[(self.width * y + x) as usize]
+    }
+
+    /// Depth of every pixel, row by row. Pixels nothing was drawn on hold `DEFAULT_Z`
+    pub fn get_depth_data(&self) -> &[f32] {
+        &self.pixel_zs
+    }
+
+    /// Grayscale image of the depth buffer, normalized between the closest and the furthest
+    /// drawn pixels. Closer pixels are darker, pixels nothing was drawn on are white.
+    pub fn depth_visualization(&self) -> PixelGrid {
+        let drawn = || self.pixel_zs.iter().filter(|z| **z < DEFAULT_Z);
+        let min = drawn().cloned().fold(f32::INFINITY, f32::min);
+        let max = drawn().cloned().fold(f32::NEG_INFINITY, f32::max);
+        let range = (max - min).max(f32::EPSILON);
+
+        let mut ret = PixelGrid::new(self.width, self.height);
+        for (i, z) in self.pixel_zs.iter().enumerate() {
+            let color = if *z < DEFAULT_Z {
+                (255.0 * (z - min) / range) as u8
+            } else {
+                255
+            };
+            ret.pixel_colors[3 * i..3 * i + 3].fill(color);
+        }
+        ret
+    }
+
     pub fn clear(&mut self) {
         self.pixel_colors
             .iter_mut()
@@ -46,7 +179,90 @@ impl PixelGrid {
         self.pixel_zs.iter_mut().for_each(|x| *x = DEFAULT_Z);
     }
 
+    /// Same as `clear`, with every pixel set to `color` instead of black
+    pub fn clear_with_color(&mut self, color: [u8; 3]) {
+        self.pixel_colors
+            .chunks_exact_mut(3)
+            .for_each(|pixel| pixel.copy_from_slice(&color));
+        self.pixel_zs.fill(DEFAULT_Z);
+    }
+
+    /// Same as `clear`, only for the pixels inside of `rect`
+    pub fn clear_rect(&mut self, rect: PixelRect) {
+        for y in rect.rows() {
+            let start = (self.width * y + rect.x) as usize;
+            let end = start + rect.width as usize;
+
+            self.pixel_colors[start * 3..end * 3].fill(0);
+            self.pixel_zs[start..end].fill(DEFAULT_Z);
+        }
+    }
+
+    /// Whole grid as a rectangle
+    pub fn rect(&self) -> PixelRect {
+        PixelRect::new(0, 0, self.width, self.height)
+    }
+
     pub fn get_pixel_data(&self) -> &[u8] {
         &self.pixel_colors
     }
+
+    /// Splits the grid into bands of `tile_height` rows that can be drawn into in parallel
+    pub(crate) fn par_tiles_mut(
+        &mut self,
+        tile_height: u32,
+    ) -> impl IndexedParallelIterator<Item = PixelTile<'_>> {
+        let width = self.width;
+        let height = self.height;
+        let tile_pixels = (width * tile_height) as usize;
+
+        self.pixel_colors
+            .par_chunks_mut(tile_pixels * 3)
+            .zip(self.pixel_zs.par_chunks_mut(tile_pixels))
+            .enumerate()
+            .map(move |(i, (colors, zs))| {
+                let y_start = i as u32 * tile_height;
+                PixelTile {
+                    width,
+                    rows: y_start..(y_start + tile_height).min(height),
+                    colors,
+                    zs,
+                }
+            })
+    }
+}
+
+/// A band of full-width rows of a `PixelGrid`. Coordinates are the same as in the whole grid.
+pub(crate) struct PixelTile<'a> {
+    pub width: u32,
+    pub rows: std::ops::Range<u32>,
+    colors: &'a mut [u8],
+    zs: &'a mut [f32],
+}
+
+impl PixelTile<'_> {
+    fn index(&self, x: u32, y: u32) -> usize {
+        (self.width * (y - self.rows.start) + x) as usize
+    }
+
+    pub fn get_depth(&self, x: u32, y: u32) -> f32 {
+        self.zs[self.index(x, y)]
+    }
+
+    pub fn get_color(&self, x: u32, y: u32) -> [u8; 3] {
+        let i = 3 * self.index(x, y);
+        [self.colors[i], self.colors[i + 1], self.colors[i + 2]]
+    }
+
+    /// Overwrites only the color of the pixel, blending is up to the caller
+    pub fn write_color(&mut self, x: u32, y: u32, color: [u8; 3]) {
+        let i = self.index(x, y);
+        self.colors[3 * i..3 * i + 3].copy_from_slice(&color);
+    }
+
+    /// Overwrites only the depth of the pixel, the depth test is up to the caller
+    pub fn write_depth(&mut self, x: u32, y: u32, z: f32) {
+        let i = self.index(x, y);
+        self.zs[i] = z;
+    }
 }
@@ -1,8 +1,11 @@
 use std::fmt::Debug;
+use std::sync::Arc;
 
 use getset::{Getters, MutGetters, Setters};
 
-use rndr_math::vector::V3;
+use rndr_math::prelude::{Vertex, V2, V3};
+
+use super::{Filter, Material, RenderTarget, Sampler, Texture, Uniforms};
 
 #[derive(Getters, MutGetters, Setters)]
 pub struct FragData {
@@ -11,16 +14,302 @@ pub struct FragData {
     #[getset(get = "pub")]
     pub(crate) relative_position: V3,
 
+    /// Interpolated normal in world space, normalized
+    #[getset(get = "pub")]
+    pub(crate) normal: V3,
+    /// Normalized direction from the fragment towards the camera. The opposite of the forward direction of
+    /// orthographic cameras, whatever the position of the fragment.
+    #[getset(get = "pub")]
+    pub(crate) view_direction: V3,
+    /// Interpolated texture coordinates
+    #[getset(get = "pub")]
+    pub(crate) uv: V2,
+    /// Change of `uv` from this pixel to the one on its right, estimated over the 2x2 block of pixels it's part of
+    #[getset(get = "pub")]
+    pub(crate) uv_dx: V2,
+    /// Change of `uv` from this pixel to the one below it, estimated over the 2x2 block of pixels it's part of
+    #[getset(get = "pub")]
+    pub(crate) uv_dy: V2,
+
+    /// Distance from the camera along its forward direction, the depth in `output_pixel` is normalized
+    /// between the near and far planes instead
+    #[getset(get = "pub")]
+    pub(crate) view_depth: f32,
+
+    /// Varyings output by the vertex shader, interpolated for this fragment. All zeros without a vertex shader
+    #[getset(get = "pub")]
+    pub(crate) varyings: Varyings,
+
+    /// Depth and RGBA color of the fragment, starting as the interpolated vertex color, opaque.
+    /// The alpha is only used by objects with a `BlendMode` other than `BlendMode::Opaque`.
+    /// A depth changed by the shader goes through the depth test again before being written.
     #[getset(get = "pub", set = "pub", get_mut = "pub")]
-    pub(crate) output_pixel: (f32, [u8; 3]),
+    pub(crate) output_pixel: (f32, [u8; 4]),
+}
+
+impl FragData {
+    /// Sets the color of `output_pixel`, leaving its alpha as it is
+    pub fn set_color(&mut self, color: [u8; 3]) {
+        self.output_pixel.1[..3].copy_from_slice(&color);
+    }
+
+    /// Multiplies the alpha of `output_pixel` by `alpha`, between 0 and 1
+    pub fn fade(&mut self, alpha: f32) {
+        let faded = self.output_pixel.1[3] as f32 * alpha.clamp(0.0, 1.0);
+        self.output_pixel.1[3] = faded.round() as u8;
+    }
+
+    /// Samples `texture` at the fragment's `uv`, using its derivatives to pick the mip levels
+    pub fn sample(&self, texture: &Texture, sampler: &Sampler) -> [u8; 4] {
+        texture.sample_grad(sampler, self.uv, self.uv_dx, self.uv_dy)
+    }
 }
 
 pub trait FragShader: Debug + Sync + Send {
-    fn frag(&self, data: &mut FragData);
+    fn frag(&self, data: &mut FragData, uniforms: &Uniforms);
+
+    /// Runs once every time an object is drawn, before any of its fragments are shaded. Shaders reading
+    /// shared state can fetch it here and return the shader the fragments are shaded with instead.
+    fn prepare(&self, _uniforms: &Uniforms) -> Option<Box<dyn FragShader>> {
+        None
+    }
+}
+
+/// Maximum amount of values a vertex shader can pass to the fragment shader
+pub const MAX_VARYINGS: usize = 8;
+
+/// Values output per vertex by a vertex shader, interpolated across the triangle for the fragment shader
+pub type Varyings = [f32; MAX_VARYINGS];
+
+#[derive(Getters, MutGetters, Setters)]
+pub struct VertexData {
+    /// The vertex, before the object's transform is applied. Changes to it are only used for rendering,
+    /// the vertices of the mesh are left untouched.
+    #[getset(get = "pub", set = "pub", get_mut = "pub")]
+    pub(crate) vertex: Vertex,
+    /// Index of the vertex in the mesh
+    #[getset(get = "pub")]
+    pub(crate) index: usize,
+
+    #[getset(get = "pub", set = "pub", get_mut = "pub")]
+    pub(crate) varyings: Varyings,
+}
+
+/// Runs once per vertex before it is projected.
+///
+/// Objects with a vertex shader are only frustum culled once `MeshRenderable::bounds_padding` tells how far
+/// the shader moves the vertices.
+pub trait VertexShader: Debug + Sync + Send {
+    fn vertex(&self, data: &mut VertexData, uniforms: &Uniforms);
 }
 
 #[derive(Debug)]
 pub struct DefaultShader;
 impl FragShader for DefaultShader {
-    fn frag(&self, _data: &mut FragData) {}
+    fn frag(&self, _data: &mut FragData, _uniforms: &Uniforms) {}
+}
+
+/// Blinn-Phong shading of the vertex colors, multiplied by the texture if there's one, by the lights of the scene.
+/// The alpha of the texture is passed along.
+#[derive(Debug, Clone)]
+pub struct LitShader {
+    pub texture: Option<Arc<Texture>>,
+    pub sampler: Sampler,
+    /// Fraction of the color that is visible without any light
+    pub ambient: f32,
+    /// Strength of the highlights
+    pub specular: f32,
+    /// How focused the highlights are
+    pub shininess: f32,
+}
+
+impl Default for LitShader {
+    fn default() -> LitShader {
+        LitShader {
+            texture: None,
+            sampler: Sampler::default(),
+            ambient: 0.1,
+            specular: 0.3,
+            shininess: 32.0,
+        }
+    }
+}
+
+impl FragShader for LitShader {
+    fn frag(&self, data: &mut FragData, uniforms: &Uniforms) {
+        let ambient = self.ambient;
+        let (diffuse, specular) = blinn_phong(data, uniforms, self.shininess);
+        let diffuse = diffuse + V3::new(ambient, ambient, ambient);
+        let specular = specular * self.specular;
+
+        let mut albedo = data.output_pixel.1;
+        if let Some(ref texture) = self.texture {
+            let texel = data.sample(texture, &self.sampler);
+            albedo = std::array::from_fn(|i| (albedo[i] as u32 * texel[i] as u32 / 255) as u8);
+        }
+        let light = [
+            (diffuse.x, specular.x),
+            (diffuse.y, specular.y),
+            (diffuse.z, specular.z),
+        ];
+        data.output_pixel.1 = std::array::from_fn(|i| match i {
+            3 => albedo[3],
+            _ => (albedo[i] as f32 * light[i].0 + 255.0 * light[i].1).clamp(0.0, 255.0) as u8,
+        });
+    }
+}
+
+/// Blinn-Phong shading of the object's `Material`, the stock shader of imported meshes.
+/// The vertex colors multiply the diffuse color, objects without a material are shaded with `Material::default()`.
+/// The alpha is the opacity of the material, multiplied by the one of its diffuse texture.
+#[derive(Debug, Clone)]
+pub struct MaterialShader {
+    pub sampler: Sampler,
+    /// Fraction of the diffuse color that is visible without any light
+    pub ambient: f32,
+}
+
+impl Default for MaterialShader {
+    fn default() -> MaterialShader {
+        MaterialShader {
+            sampler: Sampler {
+                filter: Filter::Trilinear,
+                ..Sampler::default()
+            },
+            ambient: 0.1,
+        }
+    }
+}
+
+impl FragShader for MaterialShader {
+    fn frag(&self, data: &mut FragData, uniforms: &Uniforms) {
+        let default_material;
+        let material = match uniforms.material {
+            Some(material) => material,
+            None => {
+                default_material = Material::default();
+                &default_material
+            }
+        };
+
+        let ambient = self.ambient;
+        // Exported files often leave the shininess at 0, which would light the whole surface as a highlight
+        let (diffuse, specular) = blinn_phong(data, uniforms, material.shininess.max(1.0));
+        let diffuse = diffuse + V3::new(ambient, ambient, ambient);
+
+        let mut albedo =
+            to_unit(texel_color(data.output_pixel.1)).hadamard_product(to_unit(material.diffuse));
+        let mut alpha = material.opacity;
+        if let Some(ref texture) = material.diffuse_texture {
+            let texel = data.sample(texture, &self.sampler);
+            albedo = albedo.hadamard_product(to_unit(texel_color(texel)));
+            alpha *= texel[3] as f32 / 255.0;
+        }
+
+        let mut specular_color = to_unit(material.specular);
+        if let Some(ref texture) = material.specular_texture {
+            specular_color = specular_color
+                .hadamard_product(to_unit(texel_color(data.sample(texture, &self.sampler))));
+        }
+
+        let color = albedo.hadamard_product(diffuse)
+            + specular_color.hadamard_product(specular)
+            + to_unit(material.emissive);
+        data.set_color([color.x, color.y, color.z].map(|c| (c * 255.0).clamp(0.0, 255.0) as u8));
+        data.fade(alpha);
+    }
+}
+
+/// Shows the last image rendered into a `RenderTarget`, multiplied by the vertex colors. Unlit, since screens,
+/// mirrors and portals show the light of what they look at.
+#[derive(Debug, Clone)]
+pub struct RenderTargetShader {
+    pub target: Arc<RenderTarget>,
+    pub sampler: Sampler,
+}
+
+impl RenderTargetShader {
+    pub fn new(target: Arc<RenderTarget>) -> RenderTargetShader {
+        RenderTargetShader {
+            target,
+            sampler: Sampler {
+                filter: Filter::Bilinear,
+                ..Sampler::default()
+            },
+        }
+    }
+}
+
+impl FragShader for RenderTargetShader {
+    fn frag(&self, data: &mut FragData, _uniforms: &Uniforms) {
+        multiply_by_texel(data, &self.target.texture(), &self.sampler);
+    }
+
+    /// Fetches the image of the target once for the whole object
+    fn prepare(&self, _uniforms: &Uniforms) -> Option<Box<dyn FragShader>> {
+        Some(Box::new(TargetImageShader {
+            texture: self.target.texture(),
+            sampler: self.sampler,
+        }))
+    }
+}
+
+/// `RenderTargetShader` while an object is drawn, with the image of the target already fetched
+#[derive(Debug)]
+struct TargetImageShader {
+    texture: Arc<Texture>,
+    sampler: Sampler,
+}
+
+impl FragShader for TargetImageShader {
+    fn frag(&self, data: &mut FragData, _uniforms: &Uniforms) {
+        multiply_by_texel(data, &self.texture, &self.sampler);
+    }
+}
+
+fn multiply_by_texel(data: &mut FragData, texture: &Texture, sampler: &Sampler) {
+    let texel = data.sample(texture, sampler);
+    let color = data.output_pixel.1;
+    data.output_pixel.1 = std::array::from_fn(|i| (color[i] as u32 * texel[i] as u32 / 255) as u8);
+}
+
+/// Diffuse and specular light reaching the fragment, added up over every light of the scene.
+/// Faces seen from behind are lit as seen from the front, so that double sided meshes work.
+fn blinn_phong(data: &FragData, uniforms: &Uniforms, shininess: f32) -> (V3, V3) {
+    let view_direction = data.view_direction;
+
+    let normal = if data.normal.dot(view_direction) < 0.0 {
+        -data.normal
+    } else {
+        data.normal
+    };
+
+    let mut diffuse = V3::default();
+    let mut specular = V3::default();
+
+    for light in uniforms.lights {
+        let Some((to_light, color)) = light.incidence(data.space_position) else {
+            continue;
+        };
+
+        let lambert = normal.dot(to_light);
+        if lambert <= 0.0 {
+            continue;
+        }
+        diffuse += color * lambert;
+
+        let halfway = (to_light + view_direction).norm();
+        specular += color * normal.dot(halfway).max(0.0).powf(shininess);
+    }
+
+    (diffuse, specular)
+}
+
+fn texel_color(texel: [u8; 4]) -> [u8; 3] {
+    [texel[0], texel[1], texel[2]]
+}
+
+/// Color with each channel between 0 and 1
+fn to_unit(color: [u8; 3]) -> V3 {
+    V3::new(color[0] as f32, color[1] as f32, color[2] as f32) / 255.0
 }
@@ -0,0 +1,90 @@
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+
+use super::{PixelGrid, Texture, TextureError};
+
+/// Image a camera renders into instead of the frame, see `Camera::target`.
+///
+/// Shaders sample the last image rendered into it through `texture`, see `RenderTargetShader`. Targets are
+/// shared between the camera and the shaders, so they are created behind an `Arc`.
+pub struct RenderTarget {
+    pub width: u32,
+    pub height: u32,
+    /// Color of the pixels nothing was drawn on
+    pub clear_color: [u8; 3],
+    /// Last image rendered into the target
+    grid: Mutex<PixelGrid>,
+    /// Drawn into while the camera of the target renders, then swapped with `grid`. Shaders reading `grid`
+    /// from the camera of the target itself don't wait on the render that runs them.
+    scratch: Mutex<PixelGrid>,
+    /// Copy of `grid` once the camera is done rendering, replaced every frame
+    texture: RwLock<Arc<Texture>>,
+}
+
+impl RenderTarget {
+    /// Fails with `TextureError::Empty` if `width` or `height` is 0
+    pub fn new(
+        width: u32,
+        height: u32,
+        clear_color: [u8; 3],
+    ) -> Result<Arc<RenderTarget>, TextureError> {
+        let mut grid = PixelGrid::new(width, height);
+        grid.clear_with_color(clear_color);
+        let texture = Texture::from_pixel_grid(&grid)?;
+
+        Ok(Arc::new(RenderTarget {
+            width,
+            height,
+            clear_color,
+            scratch: Mutex::new(grid.clone()),
+            grid: Mutex::new(grid),
+            texture: RwLock::new(Arc::new(texture)),
+        }))
+    }
+
+    /// Last image rendered into the target, filled with `clear_color` until its camera renders.
+    /// Stays the same while the target is being rendered into, so the target can be seen by its own camera.
+    pub fn texture(&self) -> Arc<Texture> {
+        self.texture
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Copy of the colors and depths of the last image rendered into the target. Like `texture`, it stays the
+    /// same while the target is being rendered into.
+    pub fn pixel_grid(&self) -> PixelGrid {
+        self.grid
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Clears the scratch grid, lets `draw` render into it and publishes the result as the new `pixel_grid`
+    /// and `texture`
+    pub(crate) fn render(&self, draw: impl FnOnce(&mut PixelGrid)) {
+        let mut scratch = self.scratch.lock().unwrap_or_else(PoisonError::into_inner);
+        scratch.clear_with_color(self.clear_color);
+        draw(&mut scratch);
+
+        // Built before taking the grid, so that readers of `pixel_grid` don't wait on it
+        let texture =
+            Texture::from_pixel_grid(&scratch).expect("Size was checked when creating the target");
+
+        std::mem::swap(
+            &mut *self.grid.lock().unwrap_or_else(PoisonError::into_inner),
+            &mut *scratch,
+        );
+        *self.texture.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(texture);
+    }
+}
+
+impl Debug for RenderTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RenderTarget")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("clear_color", &self.clear_color)
+            .finish_non_exhaustive()
+    }
+}
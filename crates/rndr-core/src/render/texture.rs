@@ -0,0 +1,357 @@
+use std::path::Path;
+use std::sync::OnceLock;
+
+use rndr_math::prelude::V2;
+use thiserror::Error;
+
+use super::PixelGrid;
+
+#[derive(Error, Debug)]
+pub enum TextureError {
+    #[error("Could not load texture: {0}")]
+    Image(#[from] image::ImageError),
+    #[error("Texture data of {0} bytes doesn't match its {1}x{2} size")]
+    SizeMismatch(usize, u32, u32),
+    #[error("Texture of {0}x{1} has no texels")]
+    Empty(u32, u32),
+}
+
+/// RGBA image that shaders can sample, along with its mip chain
+#[derive(Debug, Clone)]
+pub struct Texture {
+    pub width: u32,
+    pub height: u32,
+    /// Full resolution image, level 0 of the mip chain
+    base: MipLevel,
+    /// Every following level, each one half the size of the previous one, down to 1x1.
+    /// Generated the first time they are needed if the texture was created without them.
+    mips: OnceLock<Vec<MipLevel>>,
+}
+
+/// One of the levels of the mip chain of a texture
+#[derive(Debug, Clone)]
+pub struct MipLevel {
+    pub width: u32,
+    pub height: u32,
+    texels: Vec<[u8; 4]>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Filter {
+    /// Takes the closest texel of the full resolution image
+    #[default]
+    Nearest,
+    /// Blends the 4 closest texels of the full resolution image
+    Bilinear,
+    /// Blends bilinear samples of the two mip levels closest to the size of the pixel on the texture.
+    /// Needs the screen space derivatives of the texture coordinates, see `Texture::sample_grad`.
+    Trilinear,
+}
+
+/// What happens with texture coordinates outside of 0..1
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum WrapMode {
+    /// The texture tiles
+    #[default]
+    Repeat,
+    /// The texture tiles, flipped every other time
+    MirroredRepeat,
+    /// The edge texels stretch
+    Clamp,
+}
+
+/// How a texture is read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sampler {
+    pub filter: Filter,
+    pub wrap: WrapMode,
+    /// Maximum amount of trilinear samples taken along the longest side of a pixel stretched on the texture.
+    /// Surfaces seen at a grazing angle stay sharp with more than 1, only used with `Filter::Trilinear`.
+    pub max_anisotropy: u32,
+}
+
+impl Default for Sampler {
+    fn default() -> Sampler {
+        Sampler {
+            filter: Filter::default(),
+            wrap: WrapMode::default(),
+            max_anisotropy: 1,
+        }
+    }
+}
+
+impl Texture {
+    /// Loads a PNG, JPEG or TGA image
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Texture, TextureError> {
+        let image = image::open(path)?.into_rgba8();
+        let (width, height) = image.dimensions();
+
+        Texture::from_level(MipLevel {
+            width,
+            height,
+            texels: image.pixels().map(|p| p.0).collect(),
+        })
+    }
+
+    /// Decodes an image file that has already been read, in any of the formats `from_file` supports
+    pub fn from_memory(bytes: &[u8]) -> Result<Texture, TextureError> {
+        let image = image::load_from_memory(bytes)?.into_rgba8();
+        let (width, height) = image.dimensions();
+
+        Texture::from_level(MipLevel {
+            width,
+            height,
+            texels: image.pixels().map(|p| p.0).collect(),
+        })
+    }
+
+    /// `data` holds the RGBA texels row by row, starting from the top
+    pub fn from_rgba(width: u32, height: u32, data: &[u8]) -> Result<Texture, TextureError> {
+        let size = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|texels| texels.checked_mul(4));
+        if size != Some(data.len()) {
+            return Err(TextureError::SizeMismatch(data.len(), width, height));
+        }
+
+        Texture::from_level(MipLevel {
+            width,
+            height,
+            texels: data
+                .chunks_exact(4)
+                .map(|c| [c[0], c[1], c[2], c[3]])
+                .collect(),
+        })
+    }
+
+    /// Opaque copy of the colors of `grid`, which can't be empty.
+    /// The mip chain is only generated once a `Filter::Trilinear` sampler reads the texture.
+    pub fn from_pixel_grid(grid: &PixelGrid) -> Result<Texture, TextureError> {
+        Texture::without_mips(MipLevel {
+            width: grid.width,
+            height: grid.height,
+            texels: grid
+                .get_pixel_data()
+                .chunks_exact(3)
+                .map(|c| [c[0], c[1], c[2], 255])
+                .collect(),
+        })
+    }
+
+    /// Generates the mip chain of `base`, which can't be empty
+    fn from_level(base: MipLevel) -> Result<Texture, TextureError> {
+        let texture = Texture::without_mips(base)?;
+        texture.mips();
+        Ok(texture)
+    }
+
+    /// Texture of `base`, which can't be empty, leaving the mip chain to be generated when it's needed
+    fn without_mips(base: MipLevel) -> Result<Texture, TextureError> {
+        if base.width == 0 || base.height == 0 {
+            return Err(TextureError::Empty(base.width, base.height));
+        }
+
+        Ok(Texture {
+            width: base.width,
+            height: base.height,
+            base,
+            mips: OnceLock::new(),
+        })
+    }
+
+    /// Levels of the mip chain after the full resolution image
+    fn mips(&self) -> &[MipLevel] {
+        self.mips.get_or_init(|| {
+            let mut mips: Vec<MipLevel> = Vec::new();
+            loop {
+                let last = mips.last().unwrap_or(&self.base);
+                if last.width <= 1 && last.height <= 1 {
+                    break;
+                }
+                let next = last.downsample();
+                mips.push(next);
+            }
+            mips
+        })
+    }
+
+    /// Texel of the full resolution image
+    pub fn texel(&self, x: u32, y: u32) -> [u8; 4] {
+        self.base.texel(x, y)
+    }
+
+    /// Amount of levels in the mip chain, including the full resolution image
+    pub fn level_count(&self) -> usize {
+        self.mips().len() + 1
+    }
+
+    pub fn level(&self, level: usize) -> Option<&MipLevel> {
+        match level {
+            0 => Some(&self.base),
+            _ => self.mips().get(level - 1),
+        }
+    }
+
+    /// Color of the texture at `uv`. Always reads the full resolution image, even with `Filter::Trilinear`.
+    pub fn sample(&self, sampler: &Sampler, uv: V2) -> [u8; 4] {
+        self.sample_grad(sampler, uv, V2::default(), V2::default())
+    }
+
+    /// Color of the texture at `uv`, where `uv_dx` and `uv_dy` are how much `uv` changes from one pixel
+    /// to the next horizontally and vertically. They pick the mip levels used by `Filter::Trilinear`.
+    pub fn sample_grad(&self, sampler: &Sampler, uv: V2, uv_dx: V2, uv_dy: V2) -> [u8; 4] {
+        let base = &self.base;
+        let color = match sampler.filter {
+            Filter::Nearest => base.nearest(sampler.wrap, uv),
+            Filter::Bilinear => base.bilinear(sampler.wrap, uv),
+            Filter::Trilinear => self.anisotropic(sampler, uv, uv_dx, uv_dy),
+        };
+
+        color.map(|c| c.round().clamp(0.0, 255.0) as u8)
+    }
+
+    /// Averages trilinear samples spread along the longest axis of the pixel's footprint on the texture
+    fn anisotropic(&self, sampler: &Sampler, uv: V2, uv_dx: V2, uv_dy: V2) -> [f32; 4] {
+        // Footprint of the pixel in texels of the full resolution image
+        let size = V2::new(self.width as f32, self.height as f32);
+        let texels_dx = V2::new(uv_dx.x * size.x, uv_dx.y * size.y).mag();
+        let texels_dy = V2::new(uv_dy.x * size.x, uv_dy.y * size.y).mag();
+
+        // Derivatives blow up on pixels next to the horizon of a surface
+        if !texels_dx.is_finite() || !texels_dy.is_finite() {
+            return self.base.bilinear(sampler.wrap, uv);
+        }
+
+        let (major, minor, axis) = if texels_dx >= texels_dy {
+            (texels_dx, texels_dy, uv_dx)
+        } else {
+            (texels_dy, texels_dx, uv_dy)
+        };
+
+        let max_anisotropy = sampler.max_anisotropy.max(1) as f32;
+        let ratio = (major / minor.max(f32::EPSILON)).clamp(1.0, max_anisotropy);
+        let samples = ratio.ceil() as u32;
+
+        // The samples cover the major axis, so each one only has to cover a part of it
+        let lod = (major / ratio).max(f32::EPSILON).log2();
+
+        let mut sum = [0.0; 4];
+        for i in 0..samples {
+            let offset = (i as f32 + 0.5) / samples as f32 - 0.5;
+            let color = self.trilinear(sampler.wrap, uv + axis * offset, lod);
+            for (sum, c) in sum.iter_mut().zip(color) {
+                *sum += c;
+            }
+        }
+        sum.map(|c| c / samples as f32)
+    }
+
+    fn trilinear(&self, wrap: WrapMode, uv: V2, lod: f32) -> [f32; 4] {
+        if lod <= 0.0 {
+            return self.base.bilinear(wrap, uv);
+        }
+
+        let last = (self.level_count() - 1) as f32;
+        let lod = lod.min(last);
+        let lower = lod.floor();
+        let t = lod - lower;
+
+        let level = |level: usize| self.level(level).expect("Level is within the mip chain");
+        let first = level(lower as usize).bilinear(wrap, uv);
+        if t == 0.0 {
+            return first;
+        }
+        let second = level(lower as usize + 1).bilinear(wrap, uv);
+
+        std::array::from_fn(|i| first[i] * (1.0 - t) + second[i] * t)
+    }
+}
+
+impl MipLevel {
+    pub fn texel(&self, x: u32, y: u32) -> [u8; 4] {
+        self.texels[self.width as usize * y as usize + x as usize]
+    }
+
+    /// Half the size, each texel is the average of the 2x2 texels it covers
+    fn downsample(&self) -> MipLevel {
+        let width = (self.width / 2).max(1);
+        let height = (self.height / 2).max(1);
+
+        let mut texels = Vec::with_capacity(width as usize * height as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let covered = [(0, 0), (1, 0), (0, 1), (1, 1)].map(|(dx, dy)| {
+                    self.texel(
+                        (x * 2 + dx).min(self.width - 1),
+                        (y * 2 + dy).min(self.height - 1),
+                    )
+                });
+                texels.push(std::array::from_fn(|i| {
+                    ((covered.iter().map(|t| t[i] as u32).sum::<u32>() + 2) / 4) as u8
+                }));
+            }
+        }
+
+        MipLevel {
+            width,
+            height,
+            texels,
+        }
+    }
+
+    /// Position of `uv` in texels
+    fn texel_coordinates(&self, uv: V2) -> (f32, f32) {
+        // Texture coordinates start at the bottom while texels start at the top
+        (uv.x * self.width as f32, (1.0 - uv.y) * self.height as f32)
+    }
+
+    fn nearest(&self, wrap: WrapMode, uv: V2) -> [f32; 4] {
+        let (x, y) = self.texel_coordinates(uv);
+        self.wrapped_texel(wrap, x.floor() as i64, y.floor() as i64)
+            .map(|c| c as f32)
+    }
+
+    fn bilinear(&self, wrap: WrapMode, uv: V2) -> [f32; 4] {
+        let (x, y) = self.texel_coordinates(uv);
+
+        // Texel centers are at half coordinates
+        let (x, y) = (x - 0.5, y - 0.5);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let top_left = self.wrapped_texel(wrap, x0, y0);
+        let top_right = self.wrapped_texel(wrap, x0 + 1, y0);
+        let bottom_left = self.wrapped_texel(wrap, x0, y0 + 1);
+        let bottom_right = self.wrapped_texel(wrap, x0 + 1, y0 + 1);
+
+        std::array::from_fn(|i| {
+            let top = top_left[i] as f32 * (1.0 - fx) + top_right[i] as f32 * fx;
+            let bottom = bottom_left[i] as f32 * (1.0 - fx) + bottom_right[i] as f32 * fx;
+            top * (1.0 - fy) + bottom * fy
+        })
+    }
+
+    fn wrapped_texel(&self, wrap: WrapMode, x: i64, y: i64) -> [u8; 4] {
+        let x = wrap_coordinate(wrap, x, self.width);
+        let y = wrap_coordinate(wrap, y, self.height);
+        self.texel(x, y)
+    }
+}
+
+fn wrap_coordinate(wrap: WrapMode, coordinate: i64, size: u32) -> u32 {
+    let size = size as i64;
+    let wrapped = match wrap {
+        WrapMode::Repeat => coordinate.rem_euclid(size),
+        WrapMode::MirroredRepeat => {
+            let period = coordinate.rem_euclid(2 * size);
+            if period < size {
+                period
+            } else {
+                2 * size - 1 - period
+            }
+        }
+        WrapMode::Clamp => coordinate.clamp(0, size - 1),
+    };
+    wrapped as u32
+}
@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+
+use rndr_math::prelude::V3;
+
+use crate::default_components::render::Light;
+
+use super::Material;
+
+/// Values that are the same for every fragment of an object, passed to shaders alongside `FragData`
+#[derive(Debug, Clone, Copy)]
+pub struct Uniforms<'a> {
+    /// Seconds since the instance was created
+    pub time: f32,
+    /// Amount of frames rendered before this one
+    pub frame: u64,
+
+    pub camera_position: V3,
+    /// Direction the camera is looking at
+    pub camera_direction: V3,
+
+    /// Every light in the scene
+    pub lights: &'a [Light],
+
+    /// Id of the object being rendered
+    pub object_id: u64,
+    /// Parameters of the object being rendered, see `MeshRenderable::shader_params`
+    pub params: &'a ShaderParams,
+    /// Material of the object being rendered, if it has one
+    pub material: Option<&'a Material>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UniformValue {
+    Float(f32),
+    Int(i32),
+    Bool(bool),
+    Vector(V3),
+    Color([u8; 3]),
+}
+
+/// Named values a shader can be parameterized with, so the same shader can be reused with different settings
+#[derive(Debug, Default, Clone)]
+pub struct ShaderParams {
+    values: HashMap<String, UniformValue>,
+}
+
+impl ShaderParams {
+    pub fn new() -> ShaderParams {
+        ShaderParams::default()
+    }
+
+    pub fn set(&mut self, name: impl Into<String>, value: impl Into<UniformValue>) {
+        self.values.insert(name.into(), value.into());
+    }
+
+    /// Builder style version of `set`
+    pub fn with(mut self, name: impl Into<String>, value: impl Into<UniformValue>) -> ShaderParams {
+        self.set(name, value);
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<UniformValue> {
+        self.values.get(name).copied()
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<UniformValue> {
+        self.values.remove(name)
+    }
+
+    /// None if there's no such value or it isn't a float
+    pub fn float(&self, name: &str) -> Option<f32> {
+        match self.get(name)? {
+            UniformValue::Float(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// None if there's no such value or it isn't an int
+    pub fn int(&self, name: &str) -> Option<i32> {
+        match self.get(name)? {
+            UniformValue::Int(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// None if there's no such value or it isn't a bool
+    pub fn bool(&self, name: &str) -> Option<bool> {
+        match self.get(name)? {
+            UniformValue::Bool(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// None if there's no such value or it isn't a vector
+    pub fn vector(&self, name: &str) -> Option<V3> {
+        match self.get(name)? {
+            UniformValue::Vector(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// None if there's no such value or it isn't a color
+    pub fn color(&self, name: &str) -> Option<[u8; 3]> {
+        match self.get(name)? {
+            UniformValue::Color(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl From<f32> for UniformValue {
+    fn from(value: f32) -> Self {
+        UniformValue::Float(value)
+    }
+}
+
+impl From<i32> for UniformValue {
+    fn from(value: i32) -> Self {
+        UniformValue::Int(value)
+    }
+}
+
+impl From<bool> for UniformValue {
+    fn from(value: bool) -> Self {
+        UniformValue::Bool(value)
+    }
+}
+
+impl From<V3> for UniformValue {
+    fn from(value: V3) -> Self {
+        UniformValue::Vector(value)
+    }
+}
+
+impl From<[u8; 3]> for UniformValue {
+    fn from(value: [u8; 3]) -> Self {
+        UniformValue::Color(value)
+    }
+}
@@ -0,0 +1,198 @@
+mod common;
+
+use rndr_core::default_components::render::{Camera, MeshRenderable, Viewport};
+use rndr_core::default_components::Transform;
+use rndr_core::prelude::{FragData, FragShader, Instance, Object, PixelGrid, Uniforms};
+use rndr_core::render::pixel::DEFAULT_Z;
+use rndr_math::prelude::V3;
+
+use common::{
+    assert_golden, camera, center_pixel, cube, is_red, pixel_at, render, render_frame, BUFF_HEIGHT,
+    BUFF_WIDTH, GREEN, RED,
+};
+
+fn with_viewport(mut camera: Object, viewport: Viewport, render_order: i32) -> Object {
+    let component = camera.component_mut::<Camera>();
+    component.viewport = Some(viewport);
+    component.render_order = render_order;
+    camera
+}
+
+/// A red cube in front of the origin, a green one behind it
+fn instance_with_cubes() -> Instance {
+    let mut instance = Instance::init_headless(BUFF_WIDTH, BUFF_HEIGHT);
+    instance.configure_mesh_rendering_system();
+    instance.register_object(cube(V3::new(10.0, 0.0, 0.0), 2.0, RED));
+    instance.register_object(cube(V3::new(-10.0, 0.0, 0.0), 2.0, GREEN));
+    instance
+}
+
+#[test]
+fn main_camera_can_be_switched() {
+    let mut instance = instance_with_cubes();
+    let front = instance.register_object(camera(true, V3::default(), V3::default()));
+    let back = instance.register_object(camera(true, V3::default(), V3::new(0.0, 0.0, 180.0)));
+
+    // The first registered camera, whatever order the objects are stored in
+    assert_eq!(instance.main_camera(), Some(front));
+    assert!(is_red(center_pixel(&render_frame(&mut instance))));
+
+    instance.set_main_camera(back);
+    assert_eq!(instance.main_camera(), Some(back));
+    assert!(!is_red(center_pixel(&render_frame(&mut instance))));
+    assert_eq!(instance.render_stats().cameras_rendered, 1);
+
+    // Objects without a camera can't be the main one
+    instance.set_main_camera(0);
+    assert_eq!(instance.main_camera(), Some(front));
+}
+
+#[test]
+fn cameras_render_into_their_viewports() {
+    let mut instance = instance_with_cubes();
+
+    let left = Viewport::new(0.0, 0.0, 0.5, 1.0);
+    let right = Viewport::new(0.5, 0.0, 0.5, 1.0);
+    let minimap = Viewport::new(0.6, 0.05, 0.35, 0.3);
+
+    instance.register_object(with_viewport(
+        camera(true, V3::new(0.0, 0.0, 15.0), V3::new(0.0, 90.0, 90.0)),
+        minimap,
+        1,
+    ));
+    instance.register_object(with_viewport(
+        camera(true, V3::default(), V3::default()),
+        left,
+        0,
+    ));
+    instance.register_object(with_viewport(
+        camera(true, V3::default(), V3::new(0.0, 0.0, 180.0)),
+        right,
+        0,
+    ));
+
+    let frame = render_frame(&mut instance);
+    assert_eq!(instance.render_stats().cameras_rendered, 3);
+
+    // Each half sees its own cube in its center
+    assert!(is_red(pixel_at(&frame, 0.25, 0.5)));
+    assert!(!is_red(pixel_at(&frame, 0.75, 0.5)));
+
+    assert_golden("camera_viewports", &frame);
+}
+
+/// Smallest rectangle containing every drawn pixel, in fractions of the frame
+fn drawn_bounds(frame: &PixelGrid) -> (f32, f32, f32, f32) {
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (u32::MAX, u32::MAX, 0, 0);
+    for y in 0..frame.height {
+        for x in 0..frame.width {
+            if frame.get_depth(x, y) < DEFAULT_Z {
+                (min_x, min_y) = (min_x.min(x), min_y.min(y));
+                (max_x, max_y) = (max_x.max(x + 1), max_y.max(y + 1));
+            }
+        }
+    }
+
+    let (width, height) = (frame.width as f32, frame.height as f32);
+    (
+        min_x as f32 / width,
+        min_y as f32 / height,
+        max_x as f32 / width,
+        max_y as f32 / height,
+    )
+}
+
+fn render_cube(width: u32, height: u32, camera: Object) -> PixelGrid {
+    let mut instance = Instance::init_headless(width, height);
+    instance.configure_mesh_rendering_system();
+    instance.register_object(cube(V3::new(10.0, 0.0, 0.0), 2.0, RED));
+    instance.register_object(camera);
+    render_frame(&mut instance)
+}
+
+#[test]
+fn projection_is_independent_of_resolution() {
+    let small = drawn_bounds(&render_cube(
+        160,
+        120,
+        camera(true, V3::default(), V3::default()),
+    ));
+    let big = drawn_bounds(&render_cube(
+        640,
+        480,
+        camera(true, V3::default(), V3::default()),
+    ));
+
+    for (small, big) in [
+        (small.0, big.0),
+        (small.1, big.1),
+        (small.2, big.2),
+        (small.3, big.3),
+    ] {
+        assert!((small - big).abs() <= 1.0 / 120.0, "{small:?} {big:?}");
+    }
+
+    // The same goes for the public projection, which also keeps the right of the camera on the right
+    let camera = Camera::new(true);
+    let transform = Transform::default();
+    let point = V3::new(10.0, -2.0, 1.0);
+    let small = camera.project_point(point, &transform, 160, 120).unwrap();
+    let big = camera.project_point(point, &transform, 640, 480).unwrap();
+    assert!(small.x > 80.0 && small.y < 60.0);
+    assert!((small.x * 4.0 - big.x).abs() < 1e-3 && (small.y * 4.0 - big.y).abs() < 1e-3);
+    assert!((small.z - 10.0).abs() < 1e-5);
+    assert!(camera.project_point(-point, &transform, 160, 120).is_none());
+}
+
+#[test]
+fn orthographic_size_is_in_world_units() {
+    let orthographic = || {
+        let mut camera = camera(false, V3::default(), V3::default());
+        camera.component_mut::<Camera>().ortho_size = 2.0;
+        camera
+    };
+
+    // The cube is 2 units tall, half of the visible height whatever the resolution
+    for (width, height) in [(160, 120), (320, 120)] {
+        let (_, top, _, bottom) = drawn_bounds(&render_cube(width, height, orthographic()));
+        assert!((bottom - top - 0.5).abs() <= 2.0 / 120.0, "{top} {bottom}");
+    }
+}
+
+/// Colors fragments by their view direction, each axis from -1 to 1 mapped to a channel
+#[derive(Debug)]
+struct ViewDirectionShader;
+
+impl FragShader for ViewDirectionShader {
+    fn frag(&self, data: &mut FragData, _uniforms: &Uniforms) {
+        let v = *data.view_direction();
+        data.set_color([v.x, v.y, v.z].map(|c| ((c + 1.0) * 127.5).round() as u8));
+    }
+}
+
+#[test]
+fn orthographic_view_direction_is_the_camera_forward() {
+    let view_directions = |perspective| {
+        let mut wall = cube(V3::new(10.0, 0.0, 0.0), 8.0, RED);
+        wall.component_mut::<MeshRenderable>().shader = Box::new(ViewDirectionShader);
+        let frame = render(vec![
+            wall,
+            camera(perspective, V3::default(), V3::default()),
+        ]);
+
+        let mut colors: Vec<[u8; 3]> = frame
+            .get_pixel_data()
+            .chunks_exact(3)
+            .zip(frame.get_depth_data())
+            .filter(|(_, z)| **z < DEFAULT_Z)
+            .map(|(c, _)| [c[0], c[1], c[2]])
+            .collect();
+        colors.sort();
+        colors.dedup();
+        colors
+    };
+
+    // Backwards, towards the camera
+    assert_eq!(view_directions(false), [[0, 128, 128]]);
+    assert!(view_directions(true).len() > 1);
+}
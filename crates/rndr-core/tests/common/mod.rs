@@ -0,0 +1,266 @@
+//! Support for golden-image tests: scenes are rendered offscreen and compared against the
+//! reference images in `tests/golden`.
+//!
+//! Run the tests with `RNDR_BLESS=1` to (re)write the references from the current output
+//! after an intentional change to the rasterizer.
+
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+
+use rndr_core::default_components::render::MeshRenderable;
+use rndr_core::default_components::Transform;
+use rndr_core::default_objects;
+use rndr_core::prelude::{FragData, FragShader, Instance, LitShader, Object, PixelGrid, Uniforms};
+use rndr_math::prelude::V3;
+
+pub const BUFF_WIDTH: u32 = 160;
+pub const BUFF_HEIGHT: u32 = 120;
+
+/// Maximum difference allowed on any channel of a pixel before it counts as mismatched
+pub const CHANNEL_TOLERANCE: u8 = 2;
+
+/// Amount of mismatched pixels still accepted, mostly to absorb edge pixels flipping
+/// because of floating point differences between platforms.
+pub const MAX_MISMATCHED_PIXELS: usize = 8;
+
+pub const RED: [u8; 3] = [255, 60, 60];
+pub const GREEN: [u8; 3] = [60, 255, 60];
+
+/// Colors fragments by their depth between `near` and `far`, tinted by `tint`.
+/// Doesn't depend on vertex colors or normals, which change with the mesh loader.
+#[derive(Debug)]
+pub struct DepthShader {
+    pub near: f32,
+    pub far: f32,
+    pub tint: [u8; 3],
+}
+
+impl FragShader for DepthShader {
+    fn frag(&self, data: &mut FragData, _uniforms: &Uniforms) {
+        let z = *data.view_depth();
+        let brightness = 1.0 - ((z - self.near) / (self.far - self.near)).clamp(0.0, 1.0);
+        let tint = self.tint;
+        data.set_color(tint.map(|c| (c as f32 * (0.2 + 0.8 * brightness)) as u8));
+    }
+}
+
+/// Checkerboard of `size` big squares over the object, in its own space.
+/// Makes any warping of the interpolated positions obvious.
+#[derive(Debug)]
+pub struct CheckerShader {
+    pub size: f32,
+}
+
+impl FragShader for CheckerShader {
+    fn frag(&self, data: &mut FragData, _uniforms: &Uniforms) {
+        let p = *data.relative_position();
+        // Offset by half a square so that faces lying on a multiple of `size` don't flicker
+        let cell = [p.x, p.y, p.z]
+            .iter()
+            .map(|c| (c / self.size + 0.5).floor())
+            .sum::<f32>();
+        data.set_color(if cell.rem_euclid(2.0) < 1.0 {
+            [255, 255, 255]
+        } else {
+            [40, 40, 40]
+        });
+    }
+}
+
+pub fn asset(name: &str) -> String {
+    format!("{}/../examples/{name}", env!("CARGO_MANIFEST_DIR"))
+}
+
+/// Path of a file in `tests/assets`, the assets only used by the tests
+pub fn test_asset(name: &str) -> String {
+    format!("{}/tests/assets/{name}", env!("CARGO_MANIFEST_DIR"))
+}
+
+/// Loads one of the example meshes at `position` with a `DepthShader`
+pub fn mesh(name: &str, position: V3, tint: [u8; 3]) -> Object {
+    let mut object = default_objects::mesh_from_file(&asset(name)).expect("Could not load mesh");
+    object.component_mut::<Transform>().position = position;
+    object.component_mut::<MeshRenderable>().shader = Box::new(DepthShader {
+        near: 0.0,
+        far: 60.0,
+        tint,
+    });
+    object
+}
+
+/// Loads one of the example meshes at `position`, white and with a `LitShader`
+pub fn lit_mesh(name: &str, position: V3) -> Object {
+    let mut object = default_objects::mesh_from_file(&asset(name)).expect("Could not load mesh");
+    object.component_mut::<Transform>().position = position;
+
+    let renderable = object.component_mut::<MeshRenderable>();
+    renderable.shader = Box::new(LitShader::default());
+    // Vertex colors depend on the order the loader outputs the vertices in
+    for vertex in renderable.vertices.iter_mut() {
+        vertex.color = [255; 3];
+    }
+    object
+}
+
+/// Cube of `size` at `position` with a `DepthShader`
+pub fn cube(position: V3, size: f32, tint: [u8; 3]) -> Object {
+    let mut cube = default_objects::cube(size);
+    cube.component_mut::<Transform>().position = position;
+    cube.component_mut::<MeshRenderable>().shader = Box::new(DepthShader {
+        near: 0.0,
+        far: 60.0,
+        tint,
+    });
+    cube
+}
+
+pub fn camera(perspective: bool, position: V3, rotation: V3) -> Object {
+    let mut camera = default_objects::camera(perspective);
+    let transform = camera.component_mut::<Transform>();
+    transform.position = position;
+    transform.rotation = rotation;
+    camera
+}
+
+/// Renders `objects` into a fresh headless instance and returns the presented frame
+pub fn render(objects: Vec<Object>) -> PixelGrid {
+    render_with(objects, |_| {})
+}
+
+/// Same as `render`, `configure` can change the instance before rendering
+pub fn render_with(objects: Vec<Object>, configure: impl FnOnce(&mut Instance)) -> PixelGrid {
+    render_instance(objects, configure)
+        .presented_frame()
+        .expect("Headless backend keeps the presented frame")
+        .clone()
+}
+
+/// Same as `render_with`, returns the instance instead of its presented frame, to check its stats
+pub fn render_instance(objects: Vec<Object>, configure: impl FnOnce(&mut Instance)) -> Instance {
+    let mut instance = Instance::init_headless(BUFF_WIDTH, BUFF_HEIGHT);
+    instance.configure_mesh_rendering_system();
+
+    for object in objects {
+        instance.register_object(object);
+    }
+
+    configure(&mut instance);
+
+    render_frame(&mut instance);
+    instance
+}
+
+/// Renders and applies a frame of an existing instance and returns it
+pub fn render_frame(instance: &mut Instance) -> PixelGrid {
+    instance.render().expect("Could not render");
+    instance.apply_render().expect("Could not apply render");
+
+    instance
+        .presented_frame()
+        .expect("Headless backend keeps the presented frame")
+        .clone()
+}
+
+/// Color of the pixel at the given fractions of the width and height of `frame`
+pub fn pixel_at(frame: &PixelGrid, x: f32, y: f32) -> [u8; 3] {
+    let (x, y) = (
+        (x * frame.width as f32) as u32,
+        (y * frame.height as f32) as u32,
+    );
+    let i = 3 * (y * frame.width + x) as usize;
+    let pixel = &frame.get_pixel_data()[i..i + 3];
+    [pixel[0], pixel[1], pixel[2]]
+}
+
+pub fn center_pixel(frame: &PixelGrid) -> [u8; 3] {
+    pixel_at(frame, 0.5, 0.5)
+}
+
+/// Whether `pixel` is tinted more by red than green, see `RED` and `GREEN`
+pub fn is_red(pixel: [u8; 3]) -> bool {
+    pixel[0] > pixel[1]
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{name}.png"))
+}
+
+fn output_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_TARGET_TMPDIR"))
+        .join("golden")
+        .join(name)
+}
+
+/// Compares `frame` against the reference image `tests/golden/<name>.png`.
+///
+/// On failure the rendered frame and a diff image (mismatched pixels in red over the dimmed
+/// reference) are written next to each other in the target directory.
+pub fn assert_golden(name: &str, frame: &PixelGrid) {
+    let golden_path = golden_path(name);
+
+    if std::env::var_os("RNDR_BLESS").is_some() {
+        frame.save(&golden_path).expect("Could not write reference");
+        return;
+    }
+
+    let golden = match image::open(&golden_path) {
+        Ok(golden) => golden.into_rgb8(),
+        Err(e) => panic!(
+            "Could not open reference {}: {e}. Run with RNDR_BLESS=1 to create it",
+            golden_path.display()
+        ),
+    };
+
+    assert_eq!(
+        (golden.width(), golden.height()),
+        (frame.width, frame.height),
+        "Reference {name} has a different size than the rendered frame"
+    );
+
+    let mut diff = PixelGrid::new(frame.width, frame.height);
+    let mut mismatched = 0;
+
+    for (i, (actual, expected)) in frame
+        .get_pixel_data()
+        .chunks_exact(3)
+        .zip(golden.as_raw().chunks_exact(3))
+        .enumerate()
+    {
+        let (x, y) = (i as u32 % frame.width, i as u32 / frame.width);
+
+        let matches = actual
+            .iter()
+            .zip(expected)
+            .all(|(a, e)| a.abs_diff(*e) <= CHANNEL_TOLERANCE);
+
+        let color = if matches {
+            let gray = (expected.iter().map(|c| *c as u32).sum::<u32>() / 12) as u8;
+            [gray; 3]
+        } else {
+            mismatched += 1;
+            [255, 0, 0]
+        };
+        diff.set_pixel(x, y, (0.0, color));
+    }
+
+    if mismatched <= MAX_MISMATCHED_PIXELS {
+        return;
+    }
+
+    let output_path = output_path(name);
+    std::fs::create_dir_all(&output_path).expect("Could not create output directory");
+    frame
+        .save(output_path.join("actual.png"))
+        .expect("Could not write rendered frame");
+    diff.save(output_path.join("diff.png"))
+        .expect("Could not write diff");
+
+    panic!(
+        "{name}: {mismatched} pixels differ from the reference (at most {MAX_MISMATCHED_PIXELS} allowed). \
+         Rendered frame and diff written to {}",
+        output_path.display()
+    );
+}
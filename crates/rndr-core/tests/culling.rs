@@ -0,0 +1,120 @@
+mod common;
+
+use rndr_core::default_components::render::{Camera, MeshRenderable};
+use rndr_core::default_components::Transform;
+use rndr_core::default_objects;
+use rndr_core::prelude::{Instance, Uniforms, VertexData, VertexShader};
+use rndr_math::prelude::V3;
+
+use common::{camera, BUFF_HEIGHT, BUFF_WIDTH};
+
+fn render_stats(cube_positions: &[V3], perspective: bool) -> (u32, u32) {
+    let mut instance = Instance::init_headless(BUFF_WIDTH, BUFF_HEIGHT);
+    instance.configure_mesh_rendering_system();
+
+    for position in cube_positions {
+        let mut cube = default_objects::cube(2.0);
+        cube.component_mut::<Transform>().position = *position;
+        instance.register_object(cube);
+    }
+    instance.register_object(camera(perspective, V3::default(), V3::default()));
+
+    instance.render().expect("Could not render");
+
+    let stats = instance.render_stats();
+    (stats.objects_rendered, stats.objects_culled)
+}
+
+#[test]
+fn objects_outside_of_the_frustum_are_culled() {
+    let stats = render_stats(
+        &[
+            // In front of the camera
+            V3::new(10.0, 0.0, 0.0),
+            // Behind it
+            V3::new(-10.0, 0.0, 0.0),
+            // Far to the sides, above and below
+            V3::new(10.0, 50.0, 0.0),
+            V3::new(10.0, -50.0, 0.0),
+            V3::new(10.0, 0.0, 50.0),
+            V3::new(10.0, 0.0, -50.0),
+        ],
+        true,
+    );
+
+    assert_eq!(stats, (1, 5));
+}
+
+#[test]
+fn partially_visible_objects_are_rendered() {
+    // Right past the left and right edges of the screen, but still overlapping it
+    let camera = Camera::new(true);
+    let aspect = camera.aspect_ratio(BUFF_WIDTH, BUFF_HEIGHT);
+    let edge = (camera.fov.to_radians() / 2.0).tan() * aspect * 10.0;
+    let stats = render_stats(
+        &[
+            V3::new(10.0, edge + 0.5, 0.0),
+            V3::new(10.0, -edge - 0.5, 0.0),
+            // Crossing the near plane
+            V3::new(0.5, 0.0, 0.0),
+        ],
+        true,
+    );
+
+    assert_eq!(stats, (3, 0));
+}
+
+#[test]
+fn orthographic_frustum() {
+    let camera = Camera::new(false);
+    let half_width = camera.ortho_size * camera.aspect_ratio(BUFF_WIDTH, BUFF_HEIGHT);
+    let stats = render_stats(
+        &[
+            V3::new(10.0, half_width - 0.5, 0.0),
+            V3::new(10.0, half_width + 5.0, 0.0),
+            V3::new(-10.0, 0.0, 0.0),
+        ],
+        false,
+    );
+
+    assert_eq!(stats, (1, 2));
+}
+
+/// Moves every vertex 20 units forward
+#[derive(Debug)]
+struct PushForward;
+
+impl VertexShader for PushForward {
+    fn vertex(&self, data: &mut VertexData, _uniforms: &Uniforms) {
+        data.vertex_mut().position.x += 20.0;
+    }
+}
+
+#[test]
+fn vertex_shaders_are_culled_with_their_padding() {
+    let render_pushed = |bounds_padding| {
+        let mut instance = Instance::init_headless(BUFF_WIDTH, BUFF_HEIGHT);
+        instance.configure_mesh_rendering_system();
+
+        // Behind the camera until the shader moves it in front of it
+        let mut cube = default_objects::cube(2.0);
+        cube.component_mut::<Transform>().position = V3::new(-10.0, 0.0, 0.0);
+        let renderable = cube.component_mut::<MeshRenderable>();
+        renderable.vertex_shader = Some(Box::new(PushForward));
+        renderable.bounds_padding = bounds_padding;
+        instance.register_object(cube);
+        instance.register_object(camera(true, V3::default(), V3::default()));
+
+        instance.render().expect("Could not render");
+        instance.apply_render().expect("Could not apply render");
+
+        let stats = instance.render_stats();
+        let frame = instance.presented_frame().unwrap();
+        let drawn = frame.get_depth(BUFF_WIDTH / 2, BUFF_HEIGHT / 2) < 1.0;
+        (stats.objects_rendered, stats.objects_culled, drawn)
+    };
+
+    assert_eq!(render_pushed(None), (1, 0, true));
+    assert_eq!(render_pushed(Some(20.0)), (1, 0, true));
+    assert_eq!(render_pushed(Some(1.0)), (0, 1, false));
+}
@@ -0,0 +1,152 @@
+mod common;
+
+use rndr_core::default_components::render::{Camera, MeshRenderable};
+use rndr_core::prelude::{DepthFunc, FragData, FragShader, Object, PixelGrid, Uniforms};
+use rndr_core::render::pixel::DEFAULT_Z;
+use rndr_math::prelude::V3;
+
+use common::{
+    camera, center_pixel, cube, is_red, render_instance, render_with, BUFF_HEIGHT, BUFF_WIDTH,
+    GREEN, RED,
+};
+
+fn depth_cube(position: V3, tint: [u8; 3], depth_func: DepthFunc) -> Object {
+    let mut cube = cube(position, 2.0, tint);
+    cube.component_mut::<MeshRenderable>().depth_func = depth_func;
+    cube
+}
+
+/// Camera at the origin looking forward
+fn camera_with_far_plane(far_plane: f32) -> Object {
+    let mut camera = camera(true, V3::default(), V3::default());
+    camera.component_mut::<Camera>().far_plane = far_plane;
+    camera
+}
+
+fn render(mut objects: Vec<Object>, far_plane: f32, depth_prepass: bool) -> PixelGrid {
+    objects.push(camera_with_far_plane(far_plane));
+    render_with(objects, |instance| {
+        instance.set_depth_prepass(depth_prepass)
+    })
+}
+
+#[test]
+fn depth_is_normalized_between_near_and_far() {
+    let frame = render(
+        vec![depth_cube(
+            V3::new(10.0, 0.0, 0.0),
+            RED,
+            DepthFunc::default(),
+        )],
+        100.0,
+        false,
+    );
+
+    // The face of the cube is 9 units in front of the camera
+    let mut camera = Camera::new(true);
+    camera.far_plane = 100.0;
+    let projection = camera.projection_matrix(camera.aspect_ratio(BUFF_WIDTH, BUFF_HEIGHT));
+    let expected = camera.project(&projection, V3::new(0.0, 0.0, 9.0)).z;
+
+    let depth = frame.get_depth(BUFF_WIDTH / 2, BUFF_HEIGHT / 2);
+    assert!(depth > 0.0 && depth < 1.0);
+    assert!((depth - expected).abs() < 1e-4, "{depth} {expected}");
+    assert_eq!(frame.get_depth(0, 0), DEFAULT_Z);
+}
+
+#[test]
+fn geometry_beyond_the_far_plane_is_clipped() {
+    let render_cube = |far_plane| {
+        render_instance(
+            vec![
+                depth_cube(V3::new(10.0, 0.0, 0.0), RED, DepthFunc::default()),
+                camera_with_far_plane(far_plane),
+            ],
+            |_| {},
+        )
+    };
+
+    // Entirely beyond it, so it isn't even sent to the renderer
+    let instance = render_cube(5.0);
+    let frame = instance.presented_frame().unwrap();
+    assert_eq!(instance.render_stats().objects_culled, 1);
+    assert!(frame.get_depth_data().iter().all(|z| *z == DEFAULT_Z));
+
+    // Crossing it, the front face is still drawn
+    let instance = render_cube(9.5);
+    let frame = instance.presented_frame().unwrap();
+    assert_eq!(instance.render_stats().objects_rendered, 1);
+    assert!(is_red(center_pixel(frame)));
+    assert!(frame.get_depth_data().iter().all(|z| *z <= DEFAULT_Z));
+}
+
+#[test]
+fn depth_funcs() {
+    for depth_prepass in [false, true] {
+        let behind = |depth_func| depth_cube(V3::new(20.0, 0.0, 0.0), RED, depth_func);
+        let front = depth_cube(V3::new(10.0, 0.0, 0.0), GREEN, DepthFunc::default());
+
+        // Overlays are drawn over what is in front of them
+        let frame = render(vec![front, behind(DepthFunc::Always)], 100.0, depth_prepass);
+        assert!(is_red(center_pixel(&frame)));
+
+        let front = depth_cube(V3::new(10.0, 0.0, 0.0), GREEN, DepthFunc::default());
+        let frame = render(vec![front, behind(DepthFunc::Less)], 100.0, depth_prepass);
+        assert!(!is_red(center_pixel(&frame)));
+
+        let frame = render(vec![behind(DepthFunc::Never)], 100.0, depth_prepass);
+        assert_eq!(center_pixel(&frame), [0; 3]);
+
+        // Of two surfaces at the same depth, the last one only ends up on top with `LessEqual`
+        let frame = render(
+            vec![
+                depth_cube(V3::new(10.0, 0.0, 0.0), GREEN, DepthFunc::Less),
+                depth_cube(V3::new(10.0, 0.0, 0.0), RED, DepthFunc::Less),
+            ],
+            100.0,
+            false,
+        );
+        assert!(!is_red(center_pixel(&frame)));
+
+        let frame = render(
+            vec![
+                depth_cube(V3::new(10.0, 0.0, 0.0), GREEN, DepthFunc::Less),
+                depth_cube(V3::new(10.0, 0.0, 0.0), RED, DepthFunc::LessEqual),
+            ],
+            100.0,
+            depth_prepass,
+        );
+        assert!(is_red(center_pixel(&frame)));
+    }
+}
+
+/// Moves every fragment right in front of the far plane
+#[derive(Debug)]
+struct PushBack;
+
+impl FragShader for PushBack {
+    fn frag(&self, data: &mut FragData, _uniforms: &Uniforms) {
+        data.output_pixel_mut().0 = 0.999;
+        data.set_color(RED);
+    }
+}
+
+#[test]
+fn depth_written_by_shaders_is_tested() {
+    let front = || depth_cube(V3::new(10.0, 0.0, 0.0), GREEN, DepthFunc::default());
+    let expected = render(vec![front()], 100.0, false);
+
+    // In front of the green cube until its shader pushes it back behind it
+    let mut pushed = depth_cube(V3::new(5.0, 0.0, 0.0), RED, DepthFunc::default());
+    pushed.component_mut::<MeshRenderable>().shader = Box::new(PushBack);
+
+    let frame = render(vec![front(), pushed], 100.0, false);
+    assert!(!is_red(center_pixel(&frame)));
+    assert_eq!(
+        frame.get_depth(BUFF_WIDTH / 2, BUFF_HEIGHT / 2),
+        expected.get_depth(BUFF_WIDTH / 2, BUFF_HEIGHT / 2)
+    );
+
+    // Where nothing was in front of it, it's drawn at the depth the shader gave it
+    assert!(frame.get_depth_data().contains(&0.999));
+}
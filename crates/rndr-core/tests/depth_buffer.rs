@@ -0,0 +1,43 @@
+use rndr_core::prelude::PixelGrid;
+use rndr_core::render::pixel::DEFAULT_Z;
+
+/// Gray value of every pixel of a depth visualization
+fn grays(visualization: &PixelGrid) -> Vec<u8> {
+    visualization
+        .get_pixel_data()
+        .chunks_exact(3)
+        .map(|c| {
+            assert!(c[1] == c[0] && c[2] == c[0]);
+            c[0]
+        })
+        .collect()
+}
+
+#[test]
+fn depth_visualization_darkens_closer_pixels() {
+    // Further and further from left to right, the last pixel is left empty
+    let mut grid = PixelGrid::new(5, 1);
+    for (x, z) in [0.25, 0.375, 0.5, 0.75].into_iter().enumerate() {
+        grid.set_pixel(x as u32, 0, (z, [255; 3]));
+    }
+    assert_eq!(grid.get_depth(4, 0), DEFAULT_Z);
+
+    let grays = grays(&grid.depth_visualization());
+    assert_eq!(grays[0], 0);
+    assert!(grays[..4].windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(grays[3], 255);
+    assert_eq!(grays[4], 255);
+}
+
+#[test]
+fn depth_visualization_without_a_depth_range() {
+    // Nothing drawn, everything is background
+    let mut grid = PixelGrid::new(3, 2);
+    assert!(grays(&grid.depth_visualization()).iter().all(|g| *g == 255));
+
+    // A single depth is the closest one
+    grid.set_pixel(1, 1, (0.5, [255; 3]));
+    let grays = grays(&grid.depth_visualization());
+    assert_eq!(grays[4], 0);
+    assert_eq!(grays.iter().filter(|g| **g == 255).count(), 5);
+}
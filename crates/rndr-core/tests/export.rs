@@ -0,0 +1,84 @@
+mod common;
+
+use std::path::{Path, PathBuf};
+
+use rndr_core::prelude::{FrameRecorder, ImageFormat, Instance, PixelGrid};
+use rndr_math::prelude::V3;
+
+use common::{camera, mesh, render, BUFF_HEIGHT, BUFF_WIDTH};
+
+fn output_dir(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"))
+        .join("export")
+        .join(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("Could not create output directory");
+    dir
+}
+
+fn frame() -> PixelGrid {
+    render(vec![
+        mesh("Cube.obj", V3::default(), [255, 120, 40]),
+        camera(true, V3::new(-8.0, -4.0, 4.0), V3::new(0.0, 20.0, 25.0)),
+    ])
+}
+
+fn assert_same_pixels(path: &Path, frame: &PixelGrid) {
+    let reloaded = image::open(path)
+        .unwrap_or_else(|e| panic!("Could not reload {}: {e}", path.display()))
+        .into_rgb8();
+
+    assert_eq!(
+        (reloaded.width(), reloaded.height()),
+        (frame.width, frame.height)
+    );
+    assert!(
+        reloaded.as_raw().as_slice() == frame.get_pixel_data(),
+        "{} doesn't match the rendered frame",
+        path.display()
+    );
+}
+
+#[test]
+fn exported_frames_reload_unchanged() {
+    let frame = frame();
+    // Make sure there is something to compare besides the background
+    assert!(frame.get_pixel_data().iter().any(|c| *c != 0));
+
+    let dir = output_dir("formats");
+    for extension in ["png", "ppm", "tga"] {
+        let path = dir.join(format!("frame.{extension}"));
+        frame.save(&path).expect("Could not export frame");
+        assert_same_pixels(&path, &frame);
+    }
+
+    assert!(frame.save(dir.join("frame.bmp")).is_err());
+}
+
+#[test]
+fn recorder_writes_every_applied_frame() {
+    let dir = output_dir("sequence");
+
+    let mut instance = Instance::init_headless(BUFF_WIDTH, BUFF_HEIGHT);
+    instance.configure_mesh_rendering_system();
+    instance.register_object(mesh("Cube.obj", V3::default(), [255, 120, 40]));
+    instance.register_object(camera(
+        true,
+        V3::new(-8.0, -4.0, 4.0),
+        V3::new(0.0, 20.0, 25.0),
+    ));
+    instance.start_recording(FrameRecorder::new(&dir, ImageFormat::Png));
+
+    for _ in 0..2 {
+        instance.render().expect("Could not render");
+        instance.apply_render().expect("Could not apply render");
+    }
+
+    let recorder = instance.stop_recording().unwrap();
+    assert_eq!(recorder.frames_recorded(), 2);
+
+    let presented = instance.presented_frame().unwrap();
+    assert_same_pixels(&dir.join("frame_0001.png"), presented);
+    assert_same_pixels(&dir.join("frame_0002.png"), presented);
+    assert!(!dir.join("frame_0003.png").exists());
+}
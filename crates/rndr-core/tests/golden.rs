@@ -0,0 +1,374 @@
+mod common;
+
+use std::sync::Arc;
+
+use rndr_core::default_components::render::{
+    CullMode, DirectionalLight, MeshRenderable, PointLight, SpotLight,
+};
+use rndr_core::default_components::Transform;
+use rndr_core::default_objects;
+use rndr_core::prelude::{
+    FragData, FragShader, LitShader, Object, ShaderParams, Uniforms, VertexData, VertexShader,
+};
+use rndr_core::render::{Filter, Sampler, Texture, WrapMode};
+use rndr_math::prelude::{V2, V3};
+
+use common::{
+    assert_golden, camera, lit_mesh, mesh, render, render_with, test_asset, CheckerShader,
+};
+
+const TEAPOT: &str = "Utah_teapot_(solid).stl";
+const CUBE: &str = "Cube.obj";
+const SPHERE: &str = "Sphere.obj";
+
+#[test]
+fn teapot_perspective() {
+    let frame = render(vec![
+        mesh(TEAPOT, V3::default(), [255, 255, 255]),
+        camera(true, V3::new(-40.0, 0.0, 4.0), V3::default()),
+    ]);
+
+    assert_golden("teapot_perspective", &frame);
+}
+
+#[test]
+fn rotated_camera() {
+    let frame = render(vec![
+        mesh(TEAPOT, V3::default(), [255, 255, 255]),
+        camera(true, V3::new(-30.0, -20.0, 20.0), V3::new(0.0, 25.0, 35.0)),
+    ]);
+
+    assert_golden("rotated_camera", &frame);
+}
+
+fn occlusion_scene() -> Vec<Object> {
+    vec![
+        mesh(TEAPOT, V3::default(), [255, 255, 255]),
+        mesh(SPHERE, V3::new(-30.0, -3.0, 4.0), [255, 80, 80]),
+        mesh(CUBE, V3::new(-32.0, 4.0, 2.0), [80, 80, 255]),
+        camera(true, V3::new(-40.0, 0.0, 4.0), V3::default()),
+    ]
+}
+
+#[test]
+fn occlusion() {
+    assert_golden("occlusion", &render(occlusion_scene()));
+}
+
+#[test]
+fn occlusion_with_depth_prepass() {
+    let frame = render_with(occlusion_scene(), |instance| {
+        instance.set_depth_prepass(true)
+    });
+
+    assert_golden("occlusion", &frame);
+}
+
+#[test]
+fn near_plane_clipping() {
+    // The faces of the cube facing the camera cross the near plane, right next to the camera
+    let frame = render(vec![
+        mesh(CUBE, V3::default(), [80, 255, 80]),
+        camera(true, V3::new(-1.05, -1.3, 0.2), V3::new(0.0, 0.0, 20.0)),
+    ]);
+
+    assert_golden("near_plane_clipping", &frame);
+}
+
+#[test]
+fn perspective_correct_interpolation() {
+    let mut cube = mesh(CUBE, V3::default(), [255, 255, 255]);
+    cube.component_mut::<MeshRenderable>().shader = Box::new(CheckerShader { size: 0.25 });
+
+    // A face seen at a grazing angle, where affine interpolation would bend the checkerboard
+    let frame = render(vec![
+        cube,
+        camera(true, V3::new(-1.6, 2.5, 0.5), V3::new(0.0, 10.0, -55.0)),
+    ]);
+
+    assert_golden("perspective_correct_interpolation", &frame);
+}
+
+#[test]
+fn front_face_culling() {
+    // Only the inside of the far faces of the cube is left
+    let mut cube = mesh(CUBE, V3::default(), [255, 255, 255]);
+    let renderable = cube.component_mut::<MeshRenderable>();
+    renderable.shader = Box::new(CheckerShader { size: 0.25 });
+    renderable.cull_mode = CullMode::Front;
+
+    let frame = render(vec![
+        cube,
+        camera(true, V3::new(-4.0, 2.0, 2.0), V3::new(0.0, 25.0, -25.0)),
+    ]);
+
+    assert_golden("front_face_culling", &frame);
+}
+
+#[test]
+fn lighting() {
+    let mut sun = default_objects::directional_light();
+    sun.component_mut::<Transform>().rotation = V3::new(0.0, 45.0, 30.0);
+    sun.component_mut::<DirectionalLight>().intensity = 0.5;
+
+    let mut point = default_objects::point_light(25.0);
+    point.component_mut::<Transform>().position = V3::new(-12.0, -6.0, 10.0);
+    point.component_mut::<PointLight>().color = [255, 60, 60];
+
+    let mut spot = default_objects::spot_light(40.0, 15.0);
+    *spot.component_mut::<Transform>() = Transform {
+        position: V3::new(-20.0, 10.0, 4.0),
+        rotation: V3::new(0.0, 0.0, -30.0),
+    };
+    spot.component_mut::<SpotLight>().color = [60, 60, 255];
+
+    let frame = render(vec![
+        lit_mesh(TEAPOT, V3::default()),
+        sun,
+        point,
+        spot,
+        camera(true, V3::new(-25.0, 0.0, 4.0), V3::default()),
+    ]);
+
+    assert_golden("lighting", &frame);
+}
+
+/// Flat color from the `tint` param, brightened on odd object ids
+#[derive(Debug)]
+struct ParamsShader;
+
+impl FragShader for ParamsShader {
+    fn frag(&self, data: &mut FragData, uniforms: &Uniforms) {
+        let tint = uniforms.params.color("tint").unwrap_or([255, 0, 255]);
+        let boost = if uniforms.object_id % 2 == 1 { 60 } else { 0 };
+        data.set_color(tint.map(|c| c.saturating_add(boost)));
+    }
+}
+
+#[test]
+fn shader_params() {
+    let cubes = [
+        (V3::new(8.0, 3.0, 0.0), [200, 40, 40]),
+        (V3::new(8.0, 0.0, 0.0), [40, 200, 40]),
+        (V3::new(8.0, -3.0, 0.0), [40, 40, 200]),
+    ]
+    .map(|(position, tint)| {
+        let mut cube = mesh(CUBE, position, [255; 3]);
+        let renderable = cube.component_mut::<MeshRenderable>();
+        renderable.shader = Box::new(ParamsShader);
+        renderable.shader_params = ShaderParams::new().with("tint", tint);
+        cube
+    });
+
+    let mut objects = Vec::from(cubes);
+    objects.push(camera(true, V3::default(), V3::default()));
+
+    assert_golden("shader_params", &render(objects));
+}
+
+/// Twists the mesh around its z axis and passes the original height on to the fragment shader
+#[derive(Debug)]
+struct TwistShader;
+
+impl VertexShader for TwistShader {
+    fn vertex(&self, data: &mut VertexData, _uniforms: &Uniforms) {
+        let vertex = data.vertex_mut();
+        let height = vertex.position.z;
+        vertex.position = vertex.position.rotate(V3::new(0.0, 0.0, 30.0 * height));
+        vertex.normal = vertex.normal.rotate(V3::new(0.0, 0.0, 30.0 * height));
+        data.varyings_mut()[0] = height;
+    }
+}
+
+/// Colors the fragments with the height passed by `TwistShader`
+#[derive(Debug)]
+struct HeightShader;
+
+impl FragShader for HeightShader {
+    fn frag(&self, data: &mut FragData, _uniforms: &Uniforms) {
+        let height = ((data.varyings()[0] + 1.0) / 2.0).clamp(0.0, 1.0);
+        data.set_color([(255.0 * height) as u8, 80, (255.0 * (1.0 - height)) as u8]);
+    }
+}
+
+#[test]
+fn vertex_shader() {
+    let mut cube = mesh(CUBE, V3::default(), [255; 3]);
+    let renderable = cube.component_mut::<MeshRenderable>();
+    renderable.shader = Box::new(HeightShader);
+    renderable.vertex_shader = Some(Box::new(TwistShader));
+
+    let frame = render(vec![
+        cube,
+        camera(true, V3::new(-4.0, 2.0, 2.0), V3::new(0.0, 25.0, -25.0)),
+    ]);
+
+    assert_golden("vertex_shader", &frame);
+}
+
+/// 4x4 texture with a different color on every texel
+fn test_texture() -> Arc<Texture> {
+    let data: Vec<u8> = (0..16u8)
+        .flat_map(|i| [(i % 4) * 80, (i / 4) * 80, 255 - i * 12, 255])
+        .collect();
+    Arc::new(Texture::from_rgba(4, 4, &data).expect("Could not create texture"))
+}
+
+#[test]
+fn textured_sphere() {
+    let mut sphere = lit_mesh(SPHERE, V3::default());
+    sphere.component_mut::<MeshRenderable>().shader = Box::new(LitShader {
+        texture: Some(test_texture()),
+        ambient: 0.3,
+        ..LitShader::default()
+    });
+
+    let mut sun = default_objects::directional_light();
+    sun.component_mut::<Transform>().rotation = V3::new(0.0, 45.0, 30.0);
+
+    let frame = render(vec![
+        sphere,
+        sun,
+        camera(true, V3::new(-3.0, 0.0, 0.0), V3::default()),
+    ]);
+
+    assert_golden("textured_sphere", &frame);
+}
+
+/// Outputs the texture as is
+#[derive(Debug)]
+struct UnlitTextureShader {
+    texture: Arc<Texture>,
+    sampler: Sampler,
+}
+
+impl FragShader for UnlitTextureShader {
+    fn frag(&self, data: &mut FragData, _uniforms: &Uniforms) {
+        let texel = data.sample(&self.texture, &self.sampler);
+        data.set_color([texel[0], texel[1], texel[2]]);
+    }
+}
+
+#[test]
+fn texture_sampling() {
+    let samplers = [
+        (Filter::Nearest, WrapMode::Repeat),
+        (Filter::Bilinear, WrapMode::Repeat),
+        (Filter::Nearest, WrapMode::Clamp),
+        (Filter::Bilinear, WrapMode::MirroredRepeat),
+    ];
+
+    let mut objects: Vec<Object> = samplers
+        .iter()
+        .enumerate()
+        .map(|(i, (filter, wrap))| {
+            let mut plane = default_objects::plane();
+            *plane.component_mut::<Transform>() = Transform {
+                position: V3::new(10.0, 3.6 - 2.4 * i as f32, 0.0),
+                rotation: V3::new(0.0, 0.0, 90.0),
+            };
+
+            let renderable = plane.component_mut::<MeshRenderable>();
+            // Goes past the texture on every side, to show the wrap mode
+            for vertex in renderable.vertices.iter_mut() {
+                vertex.uv = vertex.uv * 2.0 - V2::new(0.5, 0.5);
+            }
+            renderable.shader = Box::new(UnlitTextureShader {
+                texture: test_texture(),
+                sampler: Sampler {
+                    filter: *filter,
+                    wrap: *wrap,
+                    ..Sampler::default()
+                },
+            });
+            plane
+        })
+        .collect();
+    objects.push(camera(true, V3::default(), V3::default()));
+
+    assert_golden("texture_sampling", &render(objects));
+}
+
+/// Black and white checkerboard with squares of a single texel, the worst case for aliasing
+fn checker_texture() -> Arc<Texture> {
+    let data: Vec<u8> = (0..64u32)
+        .flat_map(|i| {
+            let c = if (i % 8 + i / 8) % 2 == 0 { 255 } else { 0 };
+            [c, c, c, 255]
+        })
+        .collect();
+    Arc::new(Texture::from_rgba(8, 8, &data).expect("Could not create texture"))
+}
+
+/// Long strip of floor centered on `y`, going away from the camera, with the checkerboard tiled over it
+fn floor_strip(y: f32, sampler: Sampler) -> Object {
+    let mut plane = default_objects::plane();
+
+    let renderable = plane.component_mut::<MeshRenderable>();
+    for vertex in renderable.vertices.iter_mut() {
+        let p = vertex.position;
+        vertex.position = V3::new(2.0 + (p.z + 1.0) * 19.0, y + p.x * 0.75, -1.0);
+        vertex.uv = V2::new(vertex.uv.x * 3.0, vertex.uv.y * 48.0);
+    }
+    renderable.recalculate_bounds();
+    renderable.shader = Box::new(UnlitTextureShader {
+        texture: checker_texture(),
+        sampler,
+    });
+    plane
+}
+
+#[test]
+fn texture_filtering() {
+    let samplers = [
+        Sampler {
+            filter: Filter::Bilinear,
+            ..Sampler::default()
+        },
+        Sampler {
+            filter: Filter::Trilinear,
+            ..Sampler::default()
+        },
+        Sampler {
+            filter: Filter::Trilinear,
+            max_anisotropy: 8,
+            ..Sampler::default()
+        },
+    ];
+
+    let mut objects: Vec<Object> = samplers
+        .into_iter()
+        .enumerate()
+        .map(|(i, sampler)| floor_strip(1.7 - 1.7 * i as f32, sampler))
+        .collect();
+    objects.push(camera(true, V3::default(), V3::default()));
+
+    assert_golden("texture_filtering", &render(objects));
+}
+
+#[test]
+fn imported_material() {
+    let mut crate_object =
+        default_objects::mesh_from_file(&test_asset("crate.obj")).expect("Could not load mesh");
+    crate_object.component_mut::<Transform>().rotation = V3::new(0.0, 20.0, 35.0);
+
+    let material = crate_object
+        .component::<MeshRenderable>()
+        .material
+        .clone()
+        .expect("Mesh has no material");
+    assert_eq!(material.name, "Crate");
+    assert_eq!(material.diffuse, [255, 178, 102]);
+    assert!(material.diffuse_texture.is_some());
+
+    let mut sun = default_objects::directional_light();
+    sun.component_mut::<Transform>().rotation = V3::new(0.0, 45.0, 30.0);
+
+    let frame = render(vec![
+        crate_object,
+        sun,
+        camera(true, V3::new(-5.0, 0.0, 0.0), V3::default()),
+    ]);
+
+    assert_golden("imported_material", &frame);
+}
@@ -0,0 +1,188 @@
+use std::path::{Path, PathBuf};
+
+use rndr_core::default_components::render::{MeshFormat, MeshRenderable};
+use rndr_core::default_components::Transform;
+use rndr_math::prelude::{Vertex, V3};
+
+fn output_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name)
+}
+
+fn reload(path: &Path) -> MeshRenderable {
+    MeshRenderable::from_file(path.to_str().unwrap()).expect("Could not load exported mesh")
+}
+
+fn close(a: V3, b: V3) -> bool {
+    (a - b).mag() < 1e-4
+}
+
+/// Vertices of each triangle, in order, as the importer may merge or split them
+fn corners(mesh: &MeshRenderable) -> Vec<Vertex> {
+    mesh.triangles
+        .iter()
+        .flat_map(|triangle| triangle.map(|i| mesh.vertices[i]))
+        .collect()
+}
+
+#[test]
+fn exported_meshes_import_back() {
+    let mut mesh = MeshRenderable::uv_sphere(1.0, 12, 6);
+    for (i, vertex) in mesh.vertices.iter_mut().enumerate() {
+        vertex.color = [i as u8, 100, 200];
+    }
+    let expected = corners(&mesh);
+
+    for (name, format) in [
+        ("sphere.obj", MeshFormat::Obj),
+        ("sphere.stl", MeshFormat::Stl),
+        ("sphere_ascii.stl", MeshFormat::StlAscii),
+        ("sphere.ply", MeshFormat::Ply),
+    ] {
+        let path = output_path(name);
+        mesh.save_with_format(&path, format)
+            .expect("Could not export mesh");
+
+        let reloaded = corners(&reload(&path));
+        assert_eq!(reloaded.len(), expected.len(), "{name}");
+
+        for (reloaded, expected) in reloaded.iter().zip(expected.iter()) {
+            assert!(close(reloaded.position, expected.position), "{name}");
+
+            // STL only has positions and flat normals
+            if matches!(format, MeshFormat::Stl | MeshFormat::StlAscii) {
+                continue;
+            }
+            assert!(close(reloaded.normal, expected.normal), "{name}");
+            assert!((reloaded.uv - expected.uv).mag() < 1e-4, "{name}");
+
+            if format == MeshFormat::Ply {
+                assert_eq!(reloaded.color, expected.color);
+            }
+        }
+    }
+}
+
+/// Checks the written file itself, without going through the importer
+#[test]
+fn ply_files_are_well_formed() {
+    let mut mesh = MeshRenderable::uv_sphere(1.0, 12, 6);
+    for (i, vertex) in mesh.vertices.iter_mut().enumerate() {
+        vertex.color = [i as u8, 100, 200];
+    }
+
+    let mut bytes = Vec::new();
+    mesh.write(&mut bytes, MeshFormat::Ply, None)
+        .expect("Could not export mesh");
+    let text = String::from_utf8(bytes).expect("ASCII PLY isn't valid UTF-8");
+    let mut lines = text.lines();
+
+    let header: Vec<&str> = lines.by_ref().take_while(|l| *l != "end_header").collect();
+    let vertex_count = format!("element vertex {}", mesh.vertices.len());
+    let face_count = format!("element face {}", mesh.triangles.len());
+    assert_eq!(
+        header,
+        [
+            "ply",
+            "format ascii 1.0",
+            &vertex_count,
+            "property float x",
+            "property float y",
+            "property float z",
+            "property float nx",
+            "property float ny",
+            "property float nz",
+            "property float s",
+            "property float t",
+            "property uchar red",
+            "property uchar green",
+            "property uchar blue",
+            &face_count,
+            "property list uchar uint vertex_indices",
+        ]
+    );
+
+    for vertex in mesh.vertices.iter() {
+        let values: Vec<f32> = lines
+            .next()
+            .expect("Missing vertex")
+            .split(' ')
+            .map(|value| value.parse().expect("Vertex value isn't a number"))
+            .collect();
+        assert_eq!(values.len(), 11);
+
+        let v3 = |i: usize| V3::new(values[i], values[i + 1], values[i + 2]);
+        assert!(close(v3(0), vertex.position));
+        assert!(close(v3(3), vertex.normal));
+        assert!((values[6] - vertex.uv.x).abs() < 1e-4 && (values[7] - vertex.uv.y).abs() < 1e-4);
+        assert_eq!(values[8..].to_vec(), vertex.color.map(|c| c as f32));
+    }
+
+    for triangle in mesh.triangles.iter() {
+        let [a, b, c] = triangle;
+        assert_eq!(lines.next(), Some(format!("3 {a} {b} {c}").as_str()));
+    }
+    assert_eq!(lines.next(), None);
+}
+
+#[test]
+fn binary_stl_files_have_a_record_per_triangle() {
+    let mesh = MeshRenderable::cube(2.0);
+
+    let mut bytes = Vec::new();
+    mesh.write(&mut bytes, MeshFormat::Stl, None)
+        .expect("Could not export mesh");
+
+    // 80 byte header, triangle count, then 50 bytes per triangle
+    assert_eq!(bytes.len(), 84 + 50 * mesh.triangles.len());
+    let count = u32::from_le_bytes(bytes[80..84].try_into().unwrap());
+    assert_eq!(count as usize, mesh.triangles.len());
+}
+
+#[test]
+fn stl_files_are_binary_unless_asked_otherwise() {
+    let mesh = MeshRenderable::cube(2.0);
+
+    let path = output_path("cube_binary.stl");
+    mesh.save(&path).expect("Could not export mesh");
+    assert_eq!(
+        std::fs::metadata(&path).unwrap().len() as usize,
+        84 + 50 * mesh.triangles.len()
+    );
+
+    let path = output_path("cube_ascii.stl");
+    mesh.save_with_format(&path, MeshFormat::StlAscii)
+        .expect("Could not export mesh");
+    let text = std::fs::read_to_string(&path).expect("ASCII STL isn't text");
+    assert!(text.starts_with("solid"));
+    assert_eq!(text.matches("endfacet").count(), mesh.triangles.len());
+}
+
+#[test]
+fn transforms_are_baked() {
+    let mesh = MeshRenderable::cube(2.0);
+    let transform = Transform {
+        position: V3::new(5.0, -2.0, 1.0),
+        rotation: V3::new(0.0, 0.0, 90.0),
+    };
+
+    let path = output_path("cube_transformed.obj");
+    mesh.save_transformed(&path, &transform)
+        .expect("Could not export mesh");
+
+    for (reloaded, original) in corners(&reload(&path)).iter().zip(corners(&mesh).iter()) {
+        let mut expected = *original;
+        transform.apply_to_vertex(&mut expected);
+
+        assert!(close(reloaded.position, expected.position));
+        assert!(close(
+            reloaded.normal,
+            original.normal.rotate(transform.rotation)
+        ));
+    }
+}
+
+#[test]
+fn unknown_extensions_are_rejected() {
+    let mesh = MeshRenderable::cube(1.0);
+    assert!(mesh.save(output_path("cube.xyz")).is_err());
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use std::fmt::Write;
+
+use rndr_core::default_components::render::MeshRenderable;
+
+use common::asset;
+
+const TEAPOT: &str = "Utah_teapot_(solid).stl";
+
+#[test]
+fn triangles_keep_their_vertices() {
+    let (mesh, stats) =
+        MeshRenderable::from_file_with_stats(&asset(TEAPOT)).expect("Could not load mesh");
+
+    assert_eq!(stats.meshes, 1);
+    assert_eq!(stats.vertices, mesh.vertices.len());
+    assert_eq!(stats.triangles, mesh.triangles.len());
+    assert_eq!(stats.skipped_faces, 0);
+
+    // STL only has flat normals, so a triangle picking the wrong vertices would face another way than them
+    for triangle in mesh.triangles.iter() {
+        let [a, b, c] = triangle.map(|i| mesh.vertices[i]);
+        let face_normal = (b.position - a.position).cross(c.position - a.position);
+        if face_normal.mag() < 1e-6 {
+            continue;
+        }
+
+        for vertex in [a, b, c] {
+            assert!(face_normal.norm().dot(vertex.normal) > 0.9);
+        }
+    }
+}
+
+#[test]
+fn large_meshes_load() {
+    // Grid of SIZE x SIZE quads, just over a million triangles once split
+    const SIZE: usize = 710;
+
+    let mut obj = String::new();
+    for y in 0..=SIZE {
+        for x in 0..=SIZE {
+            writeln!(obj, "v {x} {y} 0").unwrap();
+        }
+    }
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let i = y * (SIZE + 1) + x + 1;
+            writeln!(obj, "f {} {} {} {}", i, i + 1, i + SIZE + 2, i + SIZE + 1).unwrap();
+        }
+    }
+
+    let path = std::path::PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("grid.obj");
+    std::fs::write(&path, obj).expect("Could not write mesh");
+
+    let (mesh, stats) =
+        MeshRenderable::from_file_with_stats(path.to_str().unwrap()).expect("Could not load mesh");
+
+    assert!(stats.triangles >= 1_000_000);
+    assert_eq!(stats.triangles, SIZE * SIZE * 2);
+    assert_eq!(stats.vertices, (SIZE + 1) * (SIZE + 1));
+    assert_eq!(mesh.triangles.len(), SIZE * SIZE * 2);
+
+    // Buffers are allocated once at their final size instead of growing triangle by triangle
+    assert_eq!(mesh.vertices.capacity(), mesh.vertices.len());
+    assert_eq!(mesh.triangles.capacity(), mesh.triangles.len());
+
+    // Every triangle is one half of a unit quad, so its vertices are next to each other
+    for triangle in mesh.triangles.iter() {
+        let [a, b, c] = triangle.map(|i| mesh.vertices[i].position);
+        assert!((b - a).mag() <= 1.5 && (c - a).mag() <= 1.5);
+    }
+}
@@ -0,0 +1,152 @@
+mod common;
+
+use rndr_core::default_components::render::MeshRenderable;
+use rndr_math::prelude::{Vertex, V3};
+
+use common::asset;
+
+const TEAPOT: &str = "Utah_teapot_(solid).stl";
+
+fn face_normal(mesh: &MeshRenderable, triangle: [usize; 3]) -> V3 {
+    let [a, b, c] = triangle.map(|i| mesh.vertices[i].position);
+    (b - a).cross(c - a).norm()
+}
+
+/// Every triangle faces away from the origin, which is inside of the mesh
+fn assert_faces_outwards(mesh: &MeshRenderable) {
+    for &triangle in mesh.triangles.iter() {
+        let center = triangle
+            .map(|i| mesh.vertices[i].position)
+            .iter()
+            .fold(V3::default(), |sum, &p| sum + p);
+        assert!(face_normal(mesh, triangle).dot(center) > 0.0);
+    }
+}
+
+#[test]
+fn normals_are_split_at_sharp_edges() {
+    let mut mesh = MeshRenderable::cube(2.0);
+    mesh.weld_vertices(1e-4);
+    assert_eq!(mesh.vertices.len(), 8);
+
+    // Cube edges are at 90 degrees
+    mesh.recalculate_normals(120.0);
+    assert_eq!(mesh.vertices.len(), 8);
+    for vertex in mesh.vertices.iter() {
+        assert!((vertex.normal - vertex.position.norm()).mag() < 1e-4);
+    }
+
+    mesh.recalculate_normals(60.0);
+    assert_eq!(mesh.vertices.len(), 24);
+    for &triangle in mesh.triangles.iter() {
+        for i in triangle {
+            assert!((mesh.vertices[i].normal - face_normal(&mesh, triangle)).mag() < 1e-4);
+        }
+    }
+}
+
+#[test]
+fn welding_joins_imported_triangles() {
+    let mut mesh = MeshRenderable::from_file(&asset(TEAPOT)).expect("Could not load mesh");
+    let (vertices, triangles) = (mesh.vertices.len(), mesh.triangles.len());
+
+    // STL stores each triangle on its own, the flat normals keep them apart when imported
+    mesh.weld_vertices(1e-4);
+    mesh.recalculate_normals(45.0);
+
+    assert!(mesh.vertices.len() < vertices / 2);
+    assert!(mesh.triangles.len() <= triangles);
+    for triangle in mesh.triangles.iter() {
+        assert!(triangle.iter().all(|&i| i < mesh.vertices.len()));
+    }
+
+    // Level of detail from the welded mesh
+    mesh.decimate(triangles / 4);
+    assert!(mesh.triangles.len() <= triangles / 4);
+    assert!(mesh.triangles.len() > triangles / 8);
+}
+
+#[test]
+fn subdivision_smooths_towards_a_sphere() {
+    let mut mesh = MeshRenderable::icosphere(1.0, 0);
+    mesh.subdivide(2);
+
+    assert_eq!(mesh.triangles.len(), 20 * 16);
+    assert_faces_outwards(&mesh);
+
+    // Loop subdivision shrinks the surface a bit, but keeps it round
+    let distances: Vec<f32> = mesh.vertices.iter().map(|v| v.position.mag()).collect();
+    let (min, max) = distances
+        .iter()
+        .fold((f32::MAX, f32::MIN), |(min, max), &d| {
+            (min.min(d), max.max(d))
+        });
+    assert!(min > 0.7 && max < 1.0);
+    assert!(max - min < 0.05);
+
+    // Texture seams don't open up
+    let mut welded = MeshRenderable::new(mesh.vertices.clone(), mesh.triangles.clone());
+    welded.weld_vertices(1e-5);
+    assert_eq!(welded.vertices.len(), 2 + 10 * 16);
+}
+
+#[test]
+fn decimation_keeps_the_shape() {
+    let mut mesh = MeshRenderable::icosphere(1.0, 3);
+    assert_eq!(mesh.triangles.len(), 1280);
+
+    mesh.decimate(200);
+
+    assert!(mesh.triangles.len() <= 200 && mesh.triangles.len() > 150);
+    assert_faces_outwards(&mesh);
+    for vertex in mesh.vertices.iter() {
+        assert!((vertex.position.mag() - 1.0).abs() < 0.05);
+    }
+
+    // Still closed, each edge is shared by two triangles
+    let mut welded = MeshRenderable::new(mesh.vertices.clone(), mesh.triangles.clone());
+    welded.weld_vertices(1e-5);
+    let (v, f) = (welded.vertices.len() as i64, welded.triangles.len() as i64);
+    assert_eq!(v - f * 3 / 2 + f, 2);
+}
+
+#[test]
+fn degenerate_triangles_dont_spread_nan() {
+    let positions = [
+        // Triangle facing forward
+        V3::new(0.0, 0.0, 0.0),
+        V3::new(0.0, 1.0, 0.0),
+        V3::new(0.0, 0.0, 1.0),
+        // Halfway along its first edge, so the next triangle is a line
+        V3::new(0.0, 0.5, 0.0),
+        // Triangle collapsed to a point
+        V3::new(5.0, 5.0, 5.0),
+    ];
+    let vertices = positions
+        .iter()
+        .map(|&position| Vertex {
+            position,
+            ..Default::default()
+        })
+        .collect();
+    let mut mesh = MeshRenderable::new(vertices, vec![[0, 1, 2], [0, 3, 1], [4, 4, 4]]);
+
+    for smoothing_angle in [0.0, 60.0, 180.0] {
+        mesh.recalculate_normals(smoothing_angle);
+
+        for vertex in mesh.vertices.iter() {
+            // Vertices only touching degenerate triangles get the fallback normal
+            let expected = if vertex.position == positions[3] || vertex.position == positions[4] {
+                V3::new(0.0, 0.0, 1.0)
+            } else {
+                V3::new(1.0, 0.0, 0.0)
+            };
+            assert!(
+                (vertex.normal - expected).mag() < 1e-4,
+                "{} has normal {}",
+                vertex.position,
+                vertex.normal
+            );
+        }
+    }
+}
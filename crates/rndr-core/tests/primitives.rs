@@ -0,0 +1,92 @@
+mod common;
+
+use rndr_core::default_components::render::MeshRenderable;
+use rndr_core::default_components::Transform;
+use rndr_core::default_objects;
+use rndr_core::prelude::{LitShader, Object};
+use rndr_math::prelude::V3;
+
+use common::{assert_golden, camera, render};
+
+fn primitives() -> Vec<(&'static str, MeshRenderable)> {
+    vec![
+        ("cube", MeshRenderable::cube(2.0)),
+        ("uv_sphere", MeshRenderable::uv_sphere(1.0, 16, 8)),
+        ("icosphere", MeshRenderable::icosphere(1.0, 2)),
+        ("cylinder", MeshRenderable::cylinder(1.0, 2.0, 12)),
+        ("cone", MeshRenderable::cone(1.0, 2.0, 12)),
+        ("torus", MeshRenderable::torus(1.0, 0.3, 16, 8)),
+    ]
+}
+
+#[test]
+fn primitives_face_outwards() {
+    for (name, mesh) in primitives() {
+        for triangle in mesh.triangles.iter() {
+            let [a, b, c] = triangle.map(|i| mesh.vertices[i]);
+            let face_normal = (b.position - a.position).cross(c.position - a.position);
+            assert!(face_normal.mag() > 1e-6, "{name} has a degenerate triangle");
+
+            // Smooth normals lean away from the face, but never past its side
+            for vertex in [a, b, c] {
+                assert!(
+                    face_normal.norm().dot(vertex.normal) > 0.5,
+                    "{name} has a triangle facing inwards"
+                );
+                assert!((vertex.normal.mag() - 1.0).abs() < 1e-4);
+            }
+        }
+    }
+}
+
+#[test]
+fn spheres_are_textured_without_seams() {
+    let spheres = [
+        MeshRenderable::uv_sphere(1.0, 16, 8),
+        MeshRenderable::icosphere(1.0, 2),
+        MeshRenderable::torus(1.0, 0.3, 16, 8),
+    ];
+
+    for mesh in spheres {
+        for triangle in mesh.triangles.iter() {
+            let [a, b, c] = triangle.map(|i| mesh.vertices[i].uv.x);
+            // A triangle crossing the seam would go through most of the texture backwards
+            assert!(a.max(b).max(c) - a.min(b).min(c) < 0.5);
+        }
+    }
+}
+
+#[test]
+fn primitive_meshes() {
+    let objects: Vec<Object> = [
+        default_objects::cube(1.4),
+        default_objects::uv_sphere(0.8, 16, 8),
+        default_objects::icosphere(0.8, 1),
+        default_objects::cylinder(0.7, 1.4, 12),
+        default_objects::cone(0.7, 1.4, 12),
+        default_objects::torus(0.6, 0.25, 16, 8),
+    ]
+    .into_iter()
+    .enumerate()
+    .map(|(i, mut object)| {
+        let transform = object.component_mut::<Transform>();
+        transform.position = V3::new(
+            0.0,
+            2.0 - 2.0 * (i % 3) as f32,
+            if i < 3 { 1.0 } else { -1.0 },
+        );
+        transform.rotation = V3::new(20.0, 30.0, 0.0);
+        object.component_mut::<MeshRenderable>().shader = Box::new(LitShader::default());
+        object
+    })
+    .collect();
+
+    let mut sun = default_objects::directional_light();
+    sun.component_mut::<Transform>().rotation = V3::new(0.0, 45.0, 30.0);
+
+    let mut scene = objects;
+    scene.push(sun);
+    scene.push(camera(true, V3::new(-7.0, 0.0, 0.0), V3::default()));
+
+    assert_golden("primitive_meshes", &render(scene));
+}
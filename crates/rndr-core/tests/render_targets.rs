@@ -0,0 +1,156 @@
+mod common;
+
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+
+use rndr_core::default_components::render::{Camera, MeshRenderable};
+use rndr_core::default_components::Transform;
+use rndr_core::default_objects;
+use rndr_core::prelude::{
+    FragData, FragShader, Instance, Object, RenderTarget, RenderTargetShader, Uniforms,
+};
+use rndr_math::prelude::V3;
+
+use common::{assert_golden, camera, pixel_at, DepthShader, BUFF_HEIGHT, BUFF_WIDTH};
+
+const BLUE: [u8; 3] = [40, 40, 200];
+
+/// A security camera at the origin looking at a red cube, and a monitor behind it showing what it sees.
+/// The main camera looks at the monitor.
+fn security_camera_scene(target: &Arc<RenderTarget>) -> Instance {
+    let mut instance = Instance::init_headless(BUFF_WIDTH, BUFF_HEIGHT);
+    instance.configure_mesh_rendering_system();
+
+    // Registered first, so that it would be the main camera if it rendered into the frame
+    let mut security_camera = camera(true, V3::default(), V3::default());
+    security_camera.component_mut::<Camera>().target = Some(target.clone());
+    instance.register_object(security_camera);
+
+    let mut cube = default_objects::cube(2.0);
+    cube.component_mut::<Transform>().position = V3::new(10.0, 0.0, 0.0);
+    cube.component_mut::<MeshRenderable>().shader = Box::new(DepthShader {
+        near: 0.0,
+        far: 20.0,
+        tint: [255, 60, 60],
+    });
+    instance.register_object(cube);
+
+    let mut monitor = default_objects::cube(6.0);
+    monitor.component_mut::<Transform>().position = V3::new(-10.0, 0.0, 0.0);
+    monitor.component_mut::<MeshRenderable>().shader =
+        Box::new(RenderTargetShader::new(target.clone()));
+    instance.register_object(monitor);
+
+    instance.register_object(camera(true, V3::default(), V3::new(0.0, 0.0, 180.0)));
+    instance
+}
+
+#[test]
+fn cameras_render_into_their_target() {
+    let target = RenderTarget::new(64, 48, BLUE).expect("Could not create target");
+    assert_eq!(target.texture().texel(0, 0), [40, 40, 200, 255]);
+
+    let mut instance = security_camera_scene(&target);
+    instance.render().expect("Could not render");
+    assert_eq!(instance.render_stats().cameras_rendered, 2);
+
+    let image = target.pixel_grid();
+    assert_eq!((image.width, image.height), (64, 48));
+    assert_eq!(pixel_at(&image, 0.05, 0.05), BLUE);
+    let center = pixel_at(&image, 0.5, 0.5);
+    assert!(center[0] > center[2]);
+    assert_eq!(target.texture().texel(32, 24)[..3], center);
+}
+
+#[test]
+fn render_targets_can_be_sampled() {
+    let target = RenderTarget::new(64, 48, BLUE).expect("Could not create target");
+    let mut instance = security_camera_scene(&target);
+
+    instance.render().expect("Could not render");
+    instance.apply_render().expect("Could not apply render");
+    let frame = instance.presented_frame().unwrap().clone();
+
+    // The main camera is the one looking at the monitor, which shows the red cube over the clear color
+    let center = pixel_at(&frame, 0.5, 0.5);
+    assert!(center[0] > center[2]);
+    let edge = pixel_at(&frame, 0.3, 0.5);
+    assert!(edge[2] > edge[0]);
+
+    assert_golden("render_target", &frame);
+}
+
+#[test]
+fn cameras_with_a_target_are_never_the_main_camera() {
+    let target = RenderTarget::new(64, 48, BLUE).expect("Could not create target");
+    let mut instance = Instance::init_headless(BUFF_WIDTH, BUFF_HEIGHT);
+
+    let mut security_camera: Object = camera(true, V3::default(), V3::default());
+    security_camera.component_mut::<Camera>().target = Some(target);
+    let id = instance.register_object(security_camera);
+
+    assert_eq!(instance.main_camera(), None);
+    instance.set_main_camera(id);
+    assert_eq!(instance.main_camera(), None);
+}
+
+/// Shows the color at the center of the last image of its target, read through `RenderTarget::pixel_grid`
+/// once per draw
+#[derive(Debug)]
+struct FeedbackShader(Arc<RenderTarget>);
+
+impl FragShader for FeedbackShader {
+    fn frag(&self, data: &mut FragData, uniforms: &Uniforms) {
+        if let Some(shader) = self.prepare(uniforms) {
+            shader.frag(data, uniforms);
+        }
+    }
+
+    fn prepare(&self, _uniforms: &Uniforms) -> Option<Box<dyn FragShader>> {
+        let center = pixel_at(&self.0.pixel_grid(), 0.5, 0.5);
+        Some(Box::new(ColorShader(center)))
+    }
+}
+
+#[derive(Debug)]
+struct ColorShader([u8; 3]);
+
+impl FragShader for ColorShader {
+    fn frag(&self, data: &mut FragData, _uniforms: &Uniforms) {
+        data.set_color(self.0);
+    }
+}
+
+#[test]
+fn cameras_can_read_their_own_target() {
+    let target = RenderTarget::new(64, 48, BLUE).expect("Could not create target");
+
+    // Fails instead of hanging if the target is still locked while its camera renders
+    let (sender, receiver) = mpsc::channel();
+    let thread_target = target.clone();
+    std::thread::spawn(move || {
+        let mut instance = Instance::init_headless(BUFF_WIDTH, BUFF_HEIGHT);
+        instance.configure_mesh_rendering_system();
+
+        let mut feedback_camera = camera(true, V3::default(), V3::default());
+        feedback_camera.component_mut::<Camera>().target = Some(thread_target.clone());
+        instance.register_object(feedback_camera);
+
+        let mut screen = default_objects::cube(2.0);
+        screen.component_mut::<Transform>().position = V3::new(10.0, 0.0, 0.0);
+        screen.component_mut::<MeshRenderable>().shader = Box::new(FeedbackShader(thread_target));
+        instance.register_object(screen);
+        instance.register_object(camera(true, V3::default(), V3::new(0.0, 0.0, 180.0)));
+
+        for _ in 0..2 {
+            instance.render().expect("Could not render");
+        }
+        sender.send(()).unwrap();
+    });
+    receiver
+        .recv_timeout(Duration::from_secs(30))
+        .expect("Rendering a camera reading its own target deadlocked or failed");
+
+    // The screen shows the center of the previous image of the target, the clear color of the first one
+    assert_eq!(pixel_at(&target.pixel_grid(), 0.5, 0.5), BLUE);
+}
@@ -0,0 +1,142 @@
+mod common;
+
+use rndr_core::default_components::render::{MeshLoadError, MeshRenderable};
+use rndr_core::default_components::{SceneNode, Transform};
+use rndr_core::default_objects;
+use rndr_core::prelude::Instance;
+use rndr_math::prelude::{M4x4, V3};
+
+use common::{assert_golden, camera, render, render_with, test_asset};
+
+fn node(instance: &Instance, id: u64) -> &SceneNode {
+    instance.get_object(id).component::<SceneNode>()
+}
+
+#[test]
+fn every_node_is_imported() {
+    let mut instance = Instance::init_headless(1, 1);
+    let ids = default_objects::scene_from_file(&mut instance, &test_asset("parts.obj"))
+        .expect("Could not load scene");
+
+    // The root, both parts and the two meshes of the base, one per material
+    assert_eq!(ids.len(), 5);
+
+    let root = node(&instance, ids[0]);
+    assert_eq!(root.parent, None);
+    assert_eq!(root.children.len(), 2);
+
+    let (base, top) = (root.children[0], root.children[1]);
+    assert_eq!(node(&instance, base).name, "Base");
+    assert_eq!(node(&instance, top).name, "Top");
+
+    // Nodes with more than one mesh get a child per mesh
+    assert!(!instance.get_object(base).has_component::<MeshRenderable>());
+    let base_meshes = &node(&instance, base).children;
+    assert_eq!(base_meshes.len(), 2);
+    for id in base_meshes {
+        assert_eq!(node(&instance, *id).parent, Some(base));
+        assert!(instance.get_object(*id).has_component::<MeshRenderable>());
+    }
+
+    let top_object = instance.get_object(top);
+    assert_eq!(node(&instance, top).parent, Some(ids[0]));
+    assert!(node(&instance, top).children.is_empty());
+    let material = top_object
+        .component::<MeshRenderable>()
+        .material
+        .as_ref()
+        .expect("Mesh has no material");
+    assert_eq!(material.name, "Red");
+}
+
+#[test]
+fn failed_imports_register_nothing() {
+    let mut instance = Instance::init_headless(1, 1);
+    let result =
+        default_objects::scene_from_file(&mut instance, &test_asset("missing_texture.obj"));
+
+    assert!(matches!(result, Err(MeshLoadError::Texture(_))));
+    assert_eq!(instance.object_manager.objects_iter().count(), 0);
+}
+
+#[test]
+fn imported_scene() {
+    let mut sun = default_objects::directional_light();
+    sun.component_mut::<Transform>().rotation = V3::new(0.0, 50.0, 30.0);
+
+    let frame = render_with(
+        vec![
+            sun,
+            camera(true, V3::new(-6.0, -3.0, 3.0), V3::new(0.0, 25.0, 25.0)),
+        ],
+        |instance| {
+            default_objects::scene_from_file(instance, &test_asset("parts.obj"))
+                .expect("Could not load scene");
+        },
+    );
+
+    assert_golden("imported_scene", &frame);
+}
+
+#[test]
+fn node_transforms_are_decomposed() {
+    let rotation = V3::new(30.0, -20.0, 110.0);
+    let scale = V3::new(2.0, 0.5, 3.0);
+    let position = V3::new(1.0, -2.0, 4.0);
+
+    // Columns of the rotation matrix are the rotated axes, each one scaled
+    let axes = [
+        V3::new(1.0, 0.0, 0.0).rotate(rotation) * scale.x,
+        V3::new(0.0, 1.0, 0.0).rotate(rotation) * scale.y,
+        V3::new(0.0, 0.0, 1.0).rotate(rotation) * scale.z,
+    ];
+    let matrix = M4x4::new([
+        [axes[0].x, axes[1].x, axes[2].x, position.x],
+        [axes[0].y, axes[1].y, axes[2].y, position.y],
+        [axes[0].z, axes[1].z, axes[2].z, position.z],
+        [0.0, 0.0, 0.0, 1.0],
+    ]);
+
+    let (decomposed_position, decomposed_rotation, decomposed_scale) = matrix.decompose();
+    let decomposed_rotation = decomposed_rotation.to_euler();
+
+    let close = |a: V3, b: V3| (a - b).mag() < 1e-3;
+    assert!(close(decomposed_position, position));
+    assert!(close(decomposed_scale, scale), "{decomposed_scale}");
+    assert!(
+        close(decomposed_rotation, rotation),
+        "{decomposed_rotation}"
+    );
+}
+
+#[test]
+fn mirrored_nodes_keep_facing_outwards() {
+    // Node matrix mirroring its mesh along y, as some exporters write them
+    let matrix = M4x4::new([
+        [2.0, 0.0, 0.0, 0.0],
+        [0.0, -1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 0.0],
+        [0.0, 0.0, 0.0, 1.0],
+    ]);
+    let (_, _, scale) = matrix.decompose();
+    assert!(scale.x * scale.y * scale.z < 0.0);
+
+    let mut mesh = MeshRenderable::cube(2.0);
+    mesh.scale(scale);
+
+    for &triangle in mesh.triangles.iter() {
+        let [a, b, c] = triangle.map(|i| mesh.vertices[i].position);
+        let face_normal = (b - a).cross(c - a);
+        assert!(face_normal.dot(a + b + c) > 0.0);
+        for i in triangle {
+            assert!(face_normal.dot(mesh.vertices[i].normal) > 0.0);
+        }
+    }
+
+    // Front faces are drawn with the default back face culling
+    let mut object = default_objects::cube(2.0);
+    *object.component_mut::<MeshRenderable>() = mesh;
+    object.component_mut::<Transform>().position = V3::new(10.0, 0.0, 0.0);
+    let frame = render(vec![object, camera(true, V3::default(), V3::default())]);
+    assert!(frame.get_depth(frame.width / 2, frame.height / 2) < 1.0);
+}
@@ -0,0 +1,74 @@
+use rndr_core::prelude::PixelGrid;
+use rndr_core::render::{Filter, RenderTarget, Sampler, Texture, TextureError, WrapMode};
+use rndr_math::prelude::V2;
+
+#[test]
+fn empty_textures_are_rejected() {
+    for (width, height) in [(0, 0), (0, 4), (4, 0)] {
+        assert!(matches!(
+            Texture::from_rgba(width, height, &[]),
+            Err(TextureError::Empty(..))
+        ));
+        assert!(matches!(
+            RenderTarget::new(width, height, [0; 3]),
+            Err(TextureError::Empty(..))
+        ));
+    }
+}
+
+#[test]
+fn oversized_textures_are_a_size_mismatch() {
+    // width * height * 4 doesn't fit in a u32
+    assert!(matches!(
+        Texture::from_rgba(u32::MAX, u32::MAX, &[0; 16]),
+        Err(TextureError::SizeMismatch(16, u32::MAX, u32::MAX))
+    ));
+    assert!(matches!(
+        Texture::from_rgba(65536, 65536, &[0; 16]),
+        Err(TextureError::SizeMismatch(..))
+    ));
+}
+
+#[test]
+fn single_texel_textures_can_be_sampled() {
+    let texture = Texture::from_rgba(1, 1, &[10, 20, 30, 255]).unwrap();
+    assert_eq!(texture.level_count(), 1);
+
+    for filter in [Filter::Nearest, Filter::Bilinear, Filter::Trilinear] {
+        for wrap in [WrapMode::Repeat, WrapMode::MirroredRepeat, WrapMode::Clamp] {
+            let sampler = Sampler {
+                filter,
+                wrap,
+                ..Default::default()
+            };
+            let uv = V2::new(-3.7, 12.2);
+            let uv_d = V2::new(0.5, 0.5);
+            assert_eq!(
+                texture.sample_grad(&sampler, uv, uv_d, uv_d),
+                [10, 20, 30, 255]
+            );
+        }
+    }
+}
+
+#[test]
+fn pixel_grid_textures_generate_their_mips_when_sampled() {
+    // Black and white columns, which average to gray
+    let mut grid = PixelGrid::new(8, 8);
+    for x in (0..8).step_by(2) {
+        for y in 0..8 {
+            grid.set_pixel(x, y, (0.0, [255; 3]));
+        }
+    }
+    let texture = Texture::from_pixel_grid(&grid).unwrap();
+
+    let sampler = Sampler {
+        filter: Filter::Trilinear,
+        ..Default::default()
+    };
+    let uv_d = V2::new(1.0, 1.0);
+    let [r, g, b, a] = texture.sample_grad(&sampler, V2::new(0.5, 0.5), uv_d, uv_d);
+    assert_eq!((r, a), (128, 255));
+    assert_eq!((g, b), (r, r));
+    assert_eq!(texture.level_count(), 4);
+}
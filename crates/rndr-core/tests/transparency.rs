@@ -0,0 +1,199 @@
+mod common;
+
+use std::path::PathBuf;
+
+use rndr_core::default_components::render::MeshRenderable;
+use rndr_core::default_components::Transform;
+use rndr_core::default_objects;
+use rndr_core::prelude::{BlendMode, FragData, FragShader, Object, PixelGrid, Uniforms};
+use rndr_math::prelude::V3;
+
+use common::{assert_golden, camera, center_pixel, cube, lit_mesh, render_with};
+
+const BLUE: [u8; 4] = [40, 40, 255, 255];
+const GLASS_GREEN: [u8; 4] = [60, 255, 60, 128];
+const GLASS_RED: [u8; 4] = [255, 60, 60, 128];
+
+/// Same color and alpha for every fragment
+#[derive(Debug)]
+struct FlatShader(pub [u8; 4]);
+
+impl FragShader for FlatShader {
+    fn frag(&self, data: &mut FragData, _uniforms: &Uniforms) {
+        data.output_pixel_mut().1 = self.0;
+    }
+}
+
+/// Cube with a `FlatShader` of `color`
+fn flat_cube(position: V3, size: f32, color: [u8; 4], blend_mode: BlendMode) -> Object {
+    let mut cube = cube(position, size, [255; 3]);
+    let renderable = cube.component_mut::<MeshRenderable>();
+    renderable.shader = Box::new(FlatShader(color));
+    renderable.blend_mode = blend_mode;
+    cube
+}
+
+/// Renders `objects` seen from the origin, looking forward
+fn render(mut objects: Vec<Object>, depth_prepass: bool) -> PixelGrid {
+    objects.push(camera(true, V3::default(), V3::default()));
+    render_with(objects, |instance| {
+        instance.set_depth_prepass(depth_prepass)
+    })
+}
+
+#[test]
+fn blend_modes() {
+    let destination = [200, 100, 0];
+
+    assert_eq!(
+        BlendMode::Opaque.blend([10, 20, 30, 0], destination),
+        [10, 20, 30]
+    );
+    assert_eq!(
+        BlendMode::Alpha.blend([0, 200, 100, 255], destination),
+        [0, 200, 100]
+    );
+    assert_eq!(
+        BlendMode::Alpha.blend([0, 200, 100, 0], destination),
+        destination
+    );
+    assert_eq!(
+        BlendMode::Alpha.blend([0, 200, 100, 51], destination),
+        [160, 120, 20]
+    );
+    assert_eq!(
+        BlendMode::Additive.blend([100, 100, 100, 255], destination),
+        [255, 200, 100]
+    );
+    assert_eq!(
+        BlendMode::Multiply.blend([128, 255, 0, 255], destination),
+        [100, 100, 0]
+    );
+    assert_eq!(
+        BlendMode::Multiply.blend([0, 0, 0, 0], destination),
+        destination
+    );
+}
+
+#[test]
+fn transparent_objects_are_drawn_back_to_front() {
+    let expected = BlendMode::Alpha.blend(
+        GLASS_GREEN,
+        BlendMode::Alpha.blend(GLASS_RED, [BLUE[0], BLUE[1], BLUE[2]]),
+    );
+
+    for depth_prepass in [false, true] {
+        // Registered from the closest to the furthest, the opposite of the order they have to be drawn in
+        let frame = render(
+            vec![
+                flat_cube(V3::new(10.0, 0.0, 0.0), 2.0, GLASS_GREEN, BlendMode::Alpha),
+                flat_cube(V3::new(15.0, 0.0, 0.0), 2.0, GLASS_RED, BlendMode::Alpha),
+                flat_cube(V3::new(30.0, 0.0, 0.0), 20.0, BLUE, BlendMode::Opaque),
+            ],
+            depth_prepass,
+        );
+
+        assert_eq!(center_pixel(&frame), expected);
+    }
+}
+
+#[test]
+fn rotated_objects_are_sorted_by_their_world_center() {
+    // Its vertices are around (-8, 0, 0), the rotation moves them to 8 units in front of its position
+    let mut rotated = flat_cube(V3::new(20.0, 0.0, 0.0), 2.0, GLASS_RED, BlendMode::Alpha);
+    rotated.component_mut::<Transform>().rotation = V3::new(0.0, 0.0, 180.0);
+    let renderable = rotated.component_mut::<MeshRenderable>();
+    for vertex in renderable.vertices.iter_mut() {
+        vertex.position.x -= 8.0;
+    }
+    renderable.recalculate_bounds();
+
+    let expected = BlendMode::Alpha.blend(
+        GLASS_GREEN,
+        BlendMode::Alpha.blend(GLASS_RED, [BLUE[0], BLUE[1], BLUE[2]]),
+    );
+
+    let frame = render(
+        vec![
+            flat_cube(V3::new(16.0, 0.0, 0.0), 2.0, GLASS_GREEN, BlendMode::Alpha),
+            rotated,
+            flat_cube(V3::new(40.0, 0.0, 0.0), 20.0, BLUE, BlendMode::Opaque),
+        ],
+        false,
+    );
+
+    assert_eq!(center_pixel(&frame), expected);
+}
+
+#[test]
+fn depth_write_can_be_disabled() {
+    let front = |depth_write| {
+        let mut cube = flat_cube(V3::new(10.0, 0.0, 0.0), 2.0, GLASS_GREEN, BlendMode::Opaque);
+        cube.component_mut::<MeshRenderable>().depth_write = depth_write;
+        cube
+    };
+    let behind = || flat_cube(V3::new(20.0, 0.0, 0.0), 4.0, BLUE, BlendMode::Opaque);
+
+    // Opaque objects are drawn in registration order, the one behind only shows when the front one
+    // didn't write its depth
+    let frame = render(vec![front(true), behind()], false);
+    assert_eq!(center_pixel(&frame), [60, 255, 60]);
+
+    let frame = render(vec![front(false), behind()], false);
+    assert_eq!(center_pixel(&frame), [40, 40, 255]);
+}
+
+#[test]
+fn translucent_materials_are_blended() {
+    let directory = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+    std::fs::write(
+        directory.join("glass.mtl"),
+        "newmtl Glass\nKd 0.2 1.0 0.2\nd 0.25\n",
+    )
+    .unwrap();
+    std::fs::write(
+        directory.join("glass.obj"),
+        "mtllib glass.mtl\nusemtl Glass\nv 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n",
+    )
+    .unwrap();
+
+    let glass = MeshRenderable::from_file(directory.join("glass.obj").to_str().unwrap())
+        .expect("Could not load mesh");
+    assert_eq!(glass.blend_mode, BlendMode::Alpha);
+    assert_eq!(glass.material.as_ref().unwrap().opacity, 0.25);
+
+    let opaque = MeshRenderable::from_file(&common::test_asset("crate.obj")).unwrap();
+    assert_eq!(opaque.blend_mode, BlendMode::Opaque);
+}
+
+#[test]
+fn transparency() {
+    let mut glow = default_objects::uv_sphere(3.0, 24, 12);
+    glow.component_mut::<Transform>().position = V3::new(-10.0, 8.0, 9.0);
+    let renderable = glow.component_mut::<MeshRenderable>();
+    renderable.shader = Box::new(FlatShader([255, 160, 40, 160]));
+    renderable.blend_mode = BlendMode::Additive;
+    renderable.depth_write = false;
+
+    // A glass pane over the left of the teapot, a tinted one over its right and a glow in front of both
+    let frame = common::render(vec![
+        lit_mesh("Utah_teapot_(solid).stl", V3::default()),
+        flat_cube(
+            V3::new(-15.0, 4.0, 4.0),
+            8.0,
+            [80, 160, 255, 110],
+            BlendMode::Alpha,
+        ),
+        flat_cube(
+            V3::new(-15.0, -5.0, 4.0),
+            6.0,
+            [255, 80, 255, 255],
+            BlendMode::Multiply,
+        ),
+        glow,
+        default_objects::directional_light(),
+        camera(true, V3::new(-40.0, 0.0, 4.0), V3::default()),
+    ]);
+
+    assert_golden("transparency", &frame);
+}
@@ -61,6 +61,23 @@ impl M3x3 {
             V3::new(g_, h_, i_),
         ]))
     }
+
+    /// Euler rotation, in the same convention as `V3::rotate`, of a rotation matrix
+    pub fn to_euler(&self) -> V3 {
+        let [first, second, third] = self.columns;
+
+        let sin_b = (-first.z).clamp(-1.0, 1.0);
+        let b = sin_b.asin();
+
+        // At 90 degrees the x and z rotations spin around the same axis, so x is left at 0
+        let (x, z) = if sin_b.abs() < 1.0 - 1e-6 {
+            (second.z.atan2(third.z), first.y.atan2(first.x))
+        } else {
+            (0.0, (-second.x).atan2(second.y))
+        };
+
+        V3::new(x.to_degrees(), b.to_degrees(), z.to_degrees())
+    }
 }
 
 impl Mul<V3> for M3x3 {
@@ -76,3 +93,112 @@ impl Mul<M3x3> for V3 {
         rhs * self
     }
 }
+
+/// Transform applied to points as column vectors, with the translation in the last column. Affine, except
+/// for projections.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct M4x4 {
+    pub rows: [[f32; 4]; 4],
+}
+
+impl Default for M4x4 {
+    fn default() -> M4x4 {
+        M4x4::identity()
+    }
+}
+
+impl M4x4 {
+    pub fn new(rows: [[f32; 4]; 4]) -> M4x4 {
+        M4x4 { rows }
+    }
+
+    pub fn identity() -> M4x4 {
+        M4x4::new([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Perspective projection of a space with x to the right, y up and z forward. `fov` is the vertical field
+    /// of view in degrees, `aspect` the width over the height. Visible points end up with x and y between -1
+    /// and 1 and z between 0 at `near` and 1 at `far`, once divided by w, see `project_point`.
+    pub fn perspective(fov: f32, aspect: f32, near: f32, far: f32) -> M4x4 {
+        let y_scale = 1.0 / (fov.to_radians() / 2.0).tan();
+        let depth = far - near;
+
+        M4x4::new([
+            [y_scale / aspect, 0.0, 0.0, 0.0],
+            [0.0, y_scale, 0.0, 0.0],
+            [0.0, 0.0, far / depth, -near * far / depth],
+            [0.0, 0.0, 1.0, 0.0],
+        ])
+    }
+
+    /// Same as `perspective`, without foreshortening. `half_height` is half of the visible height, in the
+    /// units of the space being projected.
+    pub fn orthographic(half_height: f32, aspect: f32, near: f32, far: f32) -> M4x4 {
+        let depth = far - near;
+
+        M4x4::new([
+            [1.0 / (half_height * aspect), 0.0, 0.0, 0.0],
+            [0.0, 1.0 / half_height, 0.0, 0.0],
+            [0.0, 0.0, 1.0 / depth, -near / depth],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn translation(&self) -> V3 {
+        V3::new(self.rows[0][3], self.rows[1][3], self.rows[2][3])
+    }
+
+    pub fn transform_point(&self, point: V3) -> V3 {
+        let r = self.rows;
+        V3::new(
+            r[0][0] * point.x + r[0][1] * point.y + r[0][2] * point.z + r[0][3],
+            r[1][0] * point.x + r[1][1] * point.y + r[1][2] * point.z + r[1][3],
+            r[2][0] * point.x + r[2][1] * point.y + r[2][2] * point.z + r[2][3],
+        )
+    }
+
+    /// Transforms the point as (x, y, z, 1), then divides the result by its w. Needed by projections,
+    /// whose w isn't 1.
+    pub fn project_point(&self, point: V3) -> V3 {
+        let r = self.rows;
+        let w = r[3][0] * point.x + r[3][1] * point.y + r[3][2] * point.z + r[3][3];
+        self.transform_point(point) / w
+    }
+
+    /// Splits the transform into its translation, rotation and scale, applied in the reverse order.
+    /// Shear, which can come from rotating a non uniform scale, is lost.
+    pub fn decompose(&self) -> (V3, M3x3, V3) {
+        let r = self.rows;
+        let mut columns: [V3; 3] = std::array::from_fn(|i| V3::new(r[0][i], r[1][i], r[2][i]));
+        let mut scale = V3::new(columns[0].mag(), columns[1].mag(), columns[2].mag());
+
+        // A mirrored transform can't be a rotation, so the mirroring is moved to the scale
+        if columns[0].cross(columns[1]).dot(columns[2]) < 0.0 {
+            scale.x = -scale.x;
+        }
+
+        columns[0] /= scale.x;
+        columns[1] /= scale.y;
+        columns[2] /= scale.z;
+
+        (self.translation(), M3x3::new(columns), scale)
+    }
+}
+
+impl Mul<M4x4> for M4x4 {
+    type Output = M4x4;
+    fn mul(self, rhs: M4x4) -> Self::Output {
+        M4x4::new(std::array::from_fn(|row| {
+            std::array::from_fn(|column| {
+                (0..4)
+                    .map(|i| self.rows[row][i] * rhs.rows[i][column])
+                    .sum()
+            })
+        }))
+    }
+}
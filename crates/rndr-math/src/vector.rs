@@ -232,3 +232,105 @@ impl PartialEq for V3 {
             && (self.z - other.z).abs() < f32::EPSILON
     }
 }
+
+/// 2D vector, mostly used for texture coordinates
+#[derive(Debug, Default, Clone, Copy)]
+pub struct V2 {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl V2 {
+    pub fn new(x: f32, y: f32) -> V2 {
+        V2 { x, y }
+    }
+
+    pub fn mag(&self) -> f32 {
+        (self.x.powi(2) + self.y.powi(2)).sqrt()
+    }
+
+    pub fn dot(&self, other: V2) -> f32 {
+        self.x * other.x + self.y * other.y
+    }
+
+    pub fn interpolate3(v1: (V2, f32), v2: (V2, f32), v3: (V2, f32)) -> V2 {
+        v1.0 * v1.1 + v2.0 * v2.1 + v3.0 * v3.1
+    }
+}
+
+impl Display for V2 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{{ {}, {} }}", self.x, self.y)
+    }
+}
+
+impl From<[f32; 2]> for V2 {
+    fn from(value: [f32; 2]) -> Self {
+        V2::new(value[0], value[1])
+    }
+}
+
+impl Add for V2 {
+    type Output = V2;
+    fn add(self, rhs: Self) -> Self::Output {
+        V2 {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+        }
+    }
+}
+
+impl Sub for V2 {
+    type Output = V2;
+    fn sub(self, rhs: Self) -> Self::Output {
+        V2 {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+        }
+    }
+}
+
+impl AddAssign for V2 {
+    fn add_assign(&mut self, rhs: Self) {
+        self.x += rhs.x;
+        self.y += rhs.y;
+    }
+}
+
+impl SubAssign for V2 {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.x -= rhs.x;
+        self.y -= rhs.y;
+    }
+}
+
+impl Mul<f32> for V2 {
+    type Output = V2;
+    fn mul(mut self, rhs: f32) -> Self::Output {
+        self.x *= rhs;
+        self.y *= rhs;
+        self
+    }
+}
+
+impl Div<f32> for V2 {
+    type Output = V2;
+    fn div(mut self, rhs: f32) -> Self::Output {
+        self.x /= rhs;
+        self.y /= rhs;
+        self
+    }
+}
+
+impl Neg for V2 {
+    type Output = V2;
+    fn neg(self) -> Self::Output {
+        self * -1.0
+    }
+}
+
+impl PartialEq for V2 {
+    fn eq(&self, other: &Self) -> bool {
+        (self.x - other.x).abs() < f32::EPSILON && (self.y - other.y).abs() < f32::EPSILON
+    }
+}
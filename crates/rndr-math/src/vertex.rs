@@ -1,10 +1,12 @@
-use crate::prelude::V3;
+use crate::prelude::{V2, V3};
 
 #[derive(Default, Debug, Clone, Copy)]
 pub struct Vertex {
     pub position: V3,
     pub normal: V3,
     pub color: [u8; 3],
+    /// Texture coordinates, (0, 0) is the bottom left corner of the texture
+    pub uv: V2,
 }
 
 impl Vertex {
@@ -13,6 +15,7 @@ impl Vertex {
             position,
             color,
             normal,
+            ..Default::default()
         }
     }
 
@@ -34,6 +37,7 @@ impl Vertex {
 
     pub fn interpolate(v1: (Vertex, f32), v2: (Vertex, f32), v3: (Vertex, f32)) -> Vertex {
         let color = Self::interpolate_color(v1, v2, v3);
+        let uv = V2::interpolate3((v1.0.uv, v1.1), (v2.0.uv, v2.1), (v3.0.uv, v3.1));
         let n1 = (v1.0.normal, v1.1);
         let n2 = (v2.0.normal, v2.1);
         let n3 = (v3.0.normal, v3.1);
@@ -46,6 +50,7 @@ impl Vertex {
             color,
             position: V3::interpolate3(v1, v2, v3),
             normal: V3::interpolate3(n1, n2, n3),
+            uv,
             ..Default::default()
         }
     }